@@ -3,21 +3,30 @@ use std::path::Path;
 use asefile::{AsepriteFile, Tag};
 use image::DynamicImage;
 
-pub fn generate_from_file(filename: &Path) -> (Vec<DynamicImage>, Vec<Tag>) {
+pub struct Frame {
+    pub image: DynamicImage,
+    /// How long this frame is shown for in milliseconds
+    pub duration: u32,
+}
+
+pub fn generate_from_file(filename: &Path) -> (Vec<Frame>, Vec<Tag>) {
     let ase = AsepriteFile::read_file(filename).expect("Aseprite file should exist");
 
-    let mut images = Vec::new();
+    let mut frames = Vec::new();
     let mut tags = Vec::new();
 
     for frame in 0..ase.num_frames() {
-        let image = ase.frame(frame).image();
+        let frame = ase.frame(frame);
 
-        images.push(DynamicImage::ImageRgba8(image))
+        frames.push(Frame {
+            image: DynamicImage::ImageRgba8(frame.image()),
+            duration: frame.duration(),
+        })
     }
 
     for tag in 0..ase.num_tags() {
         tags.push(ase.tag(tag).clone())
     }
 
-    (images, tags)
+    (frames, tags)
 }
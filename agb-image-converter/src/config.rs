@@ -1,5 +1,5 @@
 use serde::Deserialize;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 
 use crate::{Colour, TileSize};
@@ -20,6 +20,38 @@ pub(crate) fn parse(filename: &str) -> Box<dyn Config> {
     Box::new(config)
 }
 
+pub(crate) fn parse_sprite_sheet(filename: &str) -> Box<dyn SpriteSheet> {
+    let config_toml =
+        fs::read_to_string(filename).unwrap_or_else(|_| panic!("Failed to read file {}", filename));
+
+    let config: SpriteSheetV1 = toml::from_str(&config_toml).expect("Failed to parse file");
+
+    if config.version != "1.0" {
+        panic!(
+            "Expected version of {} to be 1.0, got {}",
+            filename, config.version
+        );
+    }
+
+    Box::new(config)
+}
+
+pub(crate) fn parse_bitmap_font(filename: &str) -> Box<dyn BitmapFont> {
+    let config_toml =
+        fs::read_to_string(filename).unwrap_or_else(|_| panic!("Failed to read file {}", filename));
+
+    let config: BitmapFontV1 = toml::from_str(&config_toml).expect("Failed to parse file");
+
+    if config.version != "1.0" {
+        panic!(
+            "Expected version of {} to be 1.0, got {}",
+            filename, config.version
+        );
+    }
+
+    Box::new(config)
+}
+
 pub(crate) trait Config {
     fn crate_prefix(&self) -> String;
     fn images(&self) -> HashMap<String, &dyn Image>;
@@ -31,6 +63,34 @@ pub(crate) trait Image {
     fn tilesize(&self) -> TileSize;
 }
 
+pub(crate) trait SpriteSheet {
+    fn filename(&self) -> String;
+    fn transparent_colour(&self) -> Option<Colour>;
+    fn frame_size(&self) -> (u32, u32);
+    fn frame_duration(&self) -> u16;
+    fn collision_masks(&self) -> bool;
+    fn tags(&self) -> Vec<SpriteSheetTag>;
+}
+
+pub(crate) trait BitmapFont {
+    fn filename(&self) -> String;
+    fn glyph_size(&self) -> (u32, u32);
+    fn characters(&self) -> Vec<char>;
+    fn transparent_colour(&self) -> Option<Colour>;
+    fn marker_colour(&self) -> Option<Colour>;
+    fn baseline(&self) -> u32;
+    fn line_height(&self) -> u32;
+    fn letter_spacing(&self) -> u32;
+}
+
+pub(crate) struct SpriteSheetTag {
+    pub name: String,
+    pub start: usize,
+    pub end: usize,
+    pub direction: usize,
+    pub duration: Option<u16>,
+}
+
 #[derive(Deserialize)]
 pub struct ConfigV1 {
     version: String,
@@ -67,19 +127,7 @@ impl Image for ImageV1 {
     }
 
     fn transparent_colour(&self) -> Option<Colour> {
-        if let Some(colour) = &self.transparent_colour {
-            if colour.len() != 6 {
-                panic!("Expected colour to be 6 characters, got {}", colour);
-            }
-
-            let r = u8::from_str_radix(&colour[0..2], 16).unwrap();
-            let g = u8::from_str_radix(&colour[2..4], 16).unwrap();
-            let b = u8::from_str_radix(&colour[4..6], 16).unwrap();
-
-            return Some(Colour::from_rgb(r, g, b));
-        }
-
-        None
+        self.transparent_colour.as_deref().map(parse_colour)
     }
 
     fn tilesize(&self) -> TileSize {
@@ -106,3 +154,140 @@ impl From<TileSizeV1> for TileSize {
         }
     }
 }
+
+#[derive(Deserialize)]
+pub struct SpriteSheetV1 {
+    version: String,
+    filename: String,
+    transparent_colour: Option<String>,
+    frame_width: u32,
+    frame_height: u32,
+    frame_duration: Option<u16>,
+    #[serde(default)]
+    collision_masks: bool,
+
+    #[serde(default)]
+    tags: BTreeMap<String, SpriteSheetTagV1>,
+}
+
+impl SpriteSheet for SpriteSheetV1 {
+    fn filename(&self) -> String {
+        self.filename.clone()
+    }
+
+    fn transparent_colour(&self) -> Option<Colour> {
+        self.transparent_colour.as_deref().map(parse_colour)
+    }
+
+    fn frame_size(&self) -> (u32, u32) {
+        (self.frame_width, self.frame_height)
+    }
+
+    fn frame_duration(&self) -> u16 {
+        self.frame_duration.unwrap_or(100)
+    }
+
+    fn collision_masks(&self) -> bool {
+        self.collision_masks
+    }
+
+    fn tags(&self) -> Vec<SpriteSheetTag> {
+        self.tags
+            .iter()
+            .map(|(name, tag)| SpriteSheetTag {
+                name: name.clone(),
+                start: tag.from,
+                end: tag.to,
+                direction: tag.direction as usize,
+                duration: tag.duration,
+            })
+            .collect()
+    }
+}
+
+#[derive(Deserialize)]
+pub struct SpriteSheetTagV1 {
+    from: usize,
+    to: usize,
+    #[serde(default)]
+    direction: DirectionV1,
+    duration: Option<u16>,
+}
+
+// These must match the values of the directions in agb
+#[derive(Deserialize, Clone, Copy, Default)]
+pub enum DirectionV1 {
+    #[default]
+    #[serde(rename = "forward")]
+    Forward = 0,
+    #[serde(rename = "backward")]
+    Backward = 1,
+    #[serde(rename = "pingpong")]
+    Pingpong = 2,
+}
+
+#[derive(Deserialize)]
+pub struct BitmapFontV1 {
+    version: String,
+    filename: String,
+    glyph_width: u32,
+    glyph_height: u32,
+    characters: String,
+    transparent_colour: Option<String>,
+    marker_colour: Option<String>,
+    baseline: Option<u32>,
+    line_height: Option<u32>,
+    #[serde(default = "default_letter_spacing")]
+    letter_spacing: u32,
+}
+
+fn default_letter_spacing() -> u32 {
+    1
+}
+
+impl BitmapFont for BitmapFontV1 {
+    fn filename(&self) -> String {
+        self.filename.clone()
+    }
+
+    fn glyph_size(&self) -> (u32, u32) {
+        (self.glyph_width, self.glyph_height)
+    }
+
+    fn characters(&self) -> Vec<char> {
+        // allow the characters to be split over multiple lines in the toml file
+        self.characters.chars().filter(|&c| c != '\n').collect()
+    }
+
+    fn transparent_colour(&self) -> Option<Colour> {
+        self.transparent_colour.as_deref().map(parse_colour)
+    }
+
+    fn marker_colour(&self) -> Option<Colour> {
+        self.marker_colour.as_deref().map(parse_colour)
+    }
+
+    fn baseline(&self) -> u32 {
+        self.baseline.unwrap_or(self.glyph_height)
+    }
+
+    fn line_height(&self) -> u32 {
+        self.line_height.unwrap_or(self.glyph_height + 1)
+    }
+
+    fn letter_spacing(&self) -> u32 {
+        self.letter_spacing
+    }
+}
+
+fn parse_colour(colour: &str) -> Colour {
+    if colour.len() != 6 {
+        panic!("Expected colour to be 6 characters, got {}", colour);
+    }
+
+    let r = u8::from_str_radix(&colour[0..2], 16).unwrap();
+    let g = u8::from_str_radix(&colour[2..4], 16).unwrap();
+    let b = u8::from_str_radix(&colour[4..6], 16).unwrap();
+
+    Colour::from_rgb(r, g, b)
+}
@@ -1,9 +1,82 @@
+use crate::colour::Colour;
 use crate::ByteString;
+use image::{DynamicImage, GenericImageView};
 use quote::quote;
 
 use proc_macro2::TokenStream;
 
+/// The named character sets which can be included in a font. Printable ASCII is
+/// always included.
+const CHARACTER_SETS: &[(&str, &[(u32, u32)])] = &[
+    ("ascii", &[]),
+    ("latin1", &[(0xa0, 0xff)]),
+    (
+        "latin-extended",
+        &[
+            (0xa0, 0x17f),
+            // general punctuation, such as quotes, dashes and the ellipsis
+            (0x2010, 0x2026),
+            // the euro sign
+            (0x20ac, 0x20ac),
+        ],
+    ),
+    (
+        "kana",
+        &[
+            // CJK punctuation, hiragana and katakana
+            (0x3000, 0x30ff),
+            // half width punctuation and katakana
+            (0xff61, 0xff9f),
+        ],
+    ),
+];
+
+/// Parses the characters to include in a font from a list of character set
+/// names separated by `+`, for example `latin1 + kana`. The result is sorted and
+/// contains no duplicates.
+pub fn parse_character_sets(sets: &str) -> Result<Vec<char>, String> {
+    let mut characters: Vec<char> = (0x20..0x7f).filter_map(char::from_u32).collect();
+
+    for name in sets.split('+').map(str::trim) {
+        let (_, ranges) = CHARACTER_SETS
+            .iter()
+            .find(|(set_name, _)| *set_name == name)
+            .ok_or_else(|| {
+                let names: Vec<_> = CHARACTER_SETS.iter().map(|(name, _)| *name).collect();
+                format!(
+                    "Unknown character set '{}', expected one of {}",
+                    name,
+                    names.join(", ")
+                )
+            })?;
+
+        characters.extend(
+            ranges
+                .iter()
+                .flat_map(|&(start, end)| start..=end)
+                .filter_map(char::from_u32),
+        );
+    }
+
+    characters.sort_unstable();
+    characters.dedup();
+
+    Ok(characters)
+}
+
+/// Anti-aliased glyphs are stored with 2 bits per pixel, as one of 4 levels of
+/// coverage. A pixel is considered solid otherwise if its level is at least 2.
+fn coverage_level(value: u8) -> u8 {
+    match value {
+        0..=32 => 0,
+        33..=100 => 1,
+        101..=180 => 2,
+        _ => 3,
+    }
+}
+
 struct LetterData {
+    character: char,
     width: usize,
     height: usize,
     xmin: i32,
@@ -12,7 +85,72 @@ struct LetterData {
     rendered: Vec<u8>,
 }
 
-pub fn load_font(font_data: &[u8], pixels_per_em: f32) -> TokenStream {
+/// Packs the coverage levels of pixels into 2 bits per pixel if keeping the
+/// levels, or into 1 bit per pixel which is set for solid pixels otherwise.
+fn pack_coverage(levels: impl Iterator<Item = u8>, keep_levels: bool) -> Vec<u8> {
+    let levels: Vec<u8> = levels.collect();
+
+    if keep_levels {
+        levels
+            .chunks(4)
+            .map(|chunk| {
+                let mut output = 0u8;
+                for (i, &level) in chunk.iter().enumerate() {
+                    output |= level << (i * 2);
+                }
+
+                output
+            })
+            .collect()
+    } else {
+        levels
+            .chunks(8)
+            .map(|chunk| {
+                let mut output = 0u8;
+                for (i, &level) in chunk.iter().enumerate() {
+                    if level >= 2 {
+                        output |= 1 << i;
+                    }
+                }
+
+                output
+            })
+            .collect()
+    }
+}
+
+fn letter_tokens(
+    letter_data: &LetterData,
+    kerning: impl Iterator<Item = TokenStream>,
+) -> TokenStream {
+    let character = letter_data.character;
+    let data_raw = ByteString(&letter_data.rendered);
+    let height = letter_data.height as u8;
+    let width = letter_data.width as u8;
+    let xmin = letter_data.xmin as i8;
+    let ymin = letter_data.ymin as i8;
+    let advance_width = letter_data.advance_width.ceil() as u8;
+
+    quote!(
+        display::FontLetter::new(
+            #character,
+            #width,
+            #height,
+            #data_raw,
+            #xmin,
+            #ymin,
+            #advance_width,
+        )
+        .with_kerning(&[#(#kerning),*])
+    )
+}
+
+pub fn load_font(
+    font_data: &[u8],
+    pixels_per_em: f32,
+    characters: &[char],
+    anti_aliased: bool,
+) -> TokenStream {
     let font = fontdue::Font::from_bytes(
         font_data,
         fontdue::FontSettings {
@@ -27,27 +165,28 @@ pub fn load_font(font_data: &[u8], pixels_per_em: f32) -> TokenStream {
     let line_height = line_metrics.new_line_size as i32;
     let ascent = line_metrics.ascent as i32;
 
-    let font = (0..128)
-        .map(|i| font.rasterize(char::from_u32(i).unwrap(), pixels_per_em))
-        .map(|(metrics, bitmap)| {
+    // Characters which aren't in the font would all be rendered as the missing
+    // glyph, so they are left out and replaced when rendering instead.
+    let characters: Vec<char> = characters
+        .iter()
+        .copied()
+        .filter(|&c| font.lookup_glyph_index(c) != 0)
+        .collect();
+
+    let letters = characters
+        .iter()
+        .map(|&c| (c, font.rasterize(c, pixels_per_em)))
+        .map(|(character, (metrics, bitmap))| {
             let width = metrics.width;
             let height = metrics.height;
 
-            let rendered = bitmap
-                .chunks(8)
-                .map(|chunk| {
-                    let mut output = 0u8;
-                    for (i, &value) in chunk.iter().enumerate() {
-                        if value > 100 {
-                            output |= 1 << i;
-                        }
-                    }
-
-                    output
-                })
-                .collect();
+            let rendered = pack_coverage(
+                bitmap.iter().map(|&value| coverage_level(value)),
+                anti_aliased,
+            );
 
             LetterData {
+                character,
                 width,
                 height,
                 rendered,
@@ -57,26 +196,197 @@ pub fn load_font(font_data: &[u8], pixels_per_em: f32) -> TokenStream {
             }
         })
         .map(|letter_data| {
-            let data_raw = ByteString(&letter_data.rendered);
-            let height = letter_data.height as u8;
-            let width = letter_data.width as u8;
-            let xmin = letter_data.xmin as i8;
-            let ymin = letter_data.ymin as i8;
-            let advance_width = letter_data.advance_width.ceil() as u8;
-
-            quote!(
-                display::FontLetter::new(
-                    #width,
-                    #height,
-                    #data_raw,
-                    #xmin,
-                    #ymin,
-                    #advance_width,
-                )
-            )
+            let character = letter_data.character;
+            let kerning = characters.iter().filter_map(|&right| {
+                let kern = font
+                    .horizontal_kern(character, right, pixels_per_em)?
+                    .round() as i8;
+                (kern != 0).then_some(quote!((#right, #kern)))
+            });
+
+            letter_tokens(&letter_data, kerning)
         });
 
+    let coverage_levels = anti_aliased.then(|| quote!(.with_coverage_levels()));
+
+    quote![
+        display::Font::new(&[#(#letters),*], #line_height, #ascent)#coverage_levels
+    ]
+}
+
+pub struct BitmapFontSettings {
+    pub glyph_size: (u32, u32),
+    pub characters: Vec<char>,
+    pub transparent_colour: Option<Colour>,
+    pub marker_colour: Option<Colour>,
+    pub baseline: u32,
+    pub line_height: u32,
+    pub letter_spacing: u32,
+}
+
+/// Splits a glyph sheet into letters. The glyphs are in a grid going left to
+/// right, then top to bottom, in the same order as the characters. A glyph ends
+/// at the first pixel of the marker colour in its cell, or fills the whole cell
+/// if it doesn't contain the marker colour.
+fn bitmap_letters(sheet: &DynamicImage, settings: &BitmapFontSettings) -> Vec<LetterData> {
+    let (glyph_width, glyph_height) = settings.glyph_size;
+    let columns = sheet.width() / glyph_width;
+    let rows = sheet.height() / glyph_height;
+
+    assert!(
+        settings.characters.len() <= (columns * rows) as usize,
+        "Expected {} glyphs in the glyph sheet, but it only has space for {}",
+        settings.characters.len(),
+        columns * rows
+    );
+
+    let colour_at = |x: u32, y: u32| {
+        let pixel = sheet.get_pixel(x, y);
+        (pixel[3] >= 128).then(|| Colour::from_rgb(pixel[0], pixel[1], pixel[2]))
+    };
+
+    settings
+        .characters
+        .iter()
+        .enumerate()
+        .map(|(i, &character)| {
+            let left = (i as u32 % columns) * glyph_width;
+            let top = (i as u32 / columns) * glyph_height;
+
+            let width = (0..glyph_width)
+                .find(|&x| {
+                    settings.marker_colour.is_some()
+                        && (0..glyph_height)
+                            .any(|y| colour_at(left + x, top + y) == settings.marker_colour)
+                })
+                .unwrap_or(glyph_width);
+
+            let rendered = pack_coverage(
+                (0..glyph_height).flat_map(|y| {
+                    (0..width).map(move |x| match colour_at(left + x, top + y) {
+                        Some(colour) if Some(colour) != settings.transparent_colour => 3,
+                        _ => 0,
+                    })
+                }),
+                false,
+            );
+
+            LetterData {
+                character,
+                width: width as usize,
+                height: glyph_height as usize,
+                xmin: 0,
+                ymin: settings.baseline as i32 - glyph_height as i32,
+                advance_width: (width + settings.letter_spacing) as f32,
+                rendered,
+            }
+        })
+        .collect()
+}
+
+pub fn load_bitmap_font(sheet: &DynamicImage, settings: &BitmapFontSettings) -> TokenStream {
+    let mut letters = bitmap_letters(sheet, settings);
+
+    letters.sort_by_key(|letter| letter.character);
+    if let Some(pair) = letters
+        .windows(2)
+        .find(|pair| pair[0].character == pair[1].character)
+    {
+        panic!(
+            "The character {:?} appears more than once in the font",
+            pair[0].character
+        );
+    }
+
+    let letters = letters
+        .iter()
+        .map(|letter| letter_tokens(letter, core::iter::empty()));
+
+    let line_height = settings.line_height as i32;
+    let ascent = settings.baseline as i32;
+
     quote![
-        display::Font::new(&[#(#font),*], #line_height, #ascent)
+        display::Font::new(&[#(#letters),*], #line_height, #ascent)
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        bitmap_letters, coverage_level, pack_coverage, parse_character_sets, BitmapFontSettings,
+    };
+    use crate::colour::Colour;
+
+    #[test]
+    fn bitmap_font_widths_come_from_markers() {
+        let white = image::Rgba([255, 255, 255, 255]);
+        let red = image::Rgba([255, 0, 0, 255]);
+
+        let mut sheet = image::RgbaImage::new(8, 2);
+        // first glyph is 2 pixels wide with a marker in the third column
+        sheet.put_pixel(0, 0, white);
+        sheet.put_pixel(1, 1, white);
+        sheet.put_pixel(2, 1, red);
+        // the second glyph has no marker so is the full cell width
+        sheet.put_pixel(7, 0, white);
+        let sheet = image::DynamicImage::ImageRgba8(sheet);
+
+        let settings = BitmapFontSettings {
+            glyph_size: (4, 2),
+            characters: vec!['a', 'b'],
+            transparent_colour: None,
+            marker_colour: Some(Colour::from_rgb(255, 0, 0)),
+            baseline: 2,
+            line_height: 3,
+            letter_spacing: 1,
+        };
+
+        let letters = bitmap_letters(&sheet, &settings);
+
+        assert_eq!(letters[0].width, 2);
+        assert_eq!(letters[0].advance_width, 3.0);
+        assert_eq!(letters[0].rendered, [0b1001]);
+
+        assert_eq!(letters[1].width, 4);
+        assert_eq!(letters[1].rendered, [0b1000]);
+    }
+
+    #[test]
+    fn coverage_levels_match_1bpp_threshold() {
+        for value in 0..=255 {
+            assert_eq!(coverage_level(value) >= 2, value > 100);
+        }
+    }
+
+    #[test]
+    fn coverage_is_only_kept_for_anti_aliased_fonts() {
+        let levels = [0, 1, 2, 3, 3, 2, 1, 0, 3];
+
+        assert_eq!(
+            pack_coverage(levels.iter().copied(), true),
+            [0b11_10_01_00, 0b00_01_10_11, 0b11]
+        );
+        assert_eq!(
+            pack_coverage(levels.iter().copied(), false),
+            [0b0011_1100, 0b1]
+        );
+    }
+
+    #[test]
+    fn character_sets_are_sorted_and_include_ascii() {
+        let characters = parse_character_sets("kana + latin1").unwrap();
+
+        assert!(characters.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(characters.contains(&'A'));
+        assert!(characters.contains(&'é'));
+        assert!(characters.contains(&'ß'));
+        assert!(characters.contains(&'か'));
+        assert!(!characters.contains(&'\n'));
+        assert!(!characters.contains(&'œ'));
+    }
+
+    #[test]
+    fn unknown_character_sets_are_an_error() {
+        assert!(parse_character_sets("latin1 + klingon").is_err());
+    }
+}
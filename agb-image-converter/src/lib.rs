@@ -1,7 +1,7 @@
 use palette16::Palette16OptimisationResults;
 use proc_macro::TokenStream;
 use proc_macro2::Literal;
-use syn::parse::Parser;
+use syn::parse::{ParseStream, Parser};
 use syn::{parse_macro_input, punctuated::Punctuated, LitStr};
 use syn::{Expr, ExprLit, Lit};
 
@@ -88,14 +88,29 @@ impl ToTokens for ByteString<'_> {
 
 #[proc_macro]
 pub fn include_aseprite_inner(input: TokenStream) -> TokenStream {
-    let parser = Punctuated::<LitStr, syn::Token![,]>::parse_separated_nonempty;
-    let parsed = match parser.parse(input) {
+    let parser = |input: ParseStream| {
+        let with_collision_masks = if input.peek(syn::Ident) && input.peek2(syn::Token![;]) {
+            let option: syn::Ident = input.parse()?;
+            if option != "collision_masks" {
+                return Err(syn::Error::new(option.span(), "Expected `collision_masks`"));
+            }
+            input.parse::<syn::Token![;]>()?;
+            true
+        } else {
+            false
+        };
+
+        let filenames = Punctuated::<LitStr, syn::Token![,]>::parse_separated_nonempty(input)?;
+        Ok((with_collision_masks, filenames))
+    };
+    let (with_collision_masks, parsed) = match parser.parse(input) {
         Ok(e) => e,
         Err(e) => return e.to_compile_error().into(),
     };
 
-    let mut optimiser = palette16::Palette16Optimiser::new();
     let mut images = Vec::new();
+    let mut collision_masks = Vec::new();
+    let mut durations = Vec::new();
     let mut tags = Vec::new();
 
     let root = std::env::var("CARGO_MANIFEST_DIR").expect("Failed to get cargo manifest dir");
@@ -109,21 +124,258 @@ pub fn include_aseprite_inner(input: TokenStream) -> TokenStream {
     for filename in filenames.iter() {
         let (frames, tag) = aseprite::generate_from_file(filename);
 
-        tags.push((tag, images.len()));
+        for tag in tag.iter() {
+            let start = tag.from_frame() as usize + images.len();
+            let end = tag.to_frame() as usize + images.len();
+            let direction = tag.animation_direction() as usize;
+
+            tags.push(SpriteTag {
+                name: tag.name().to_owned(),
+                start,
+                end,
+                direction,
+            });
+        }
 
-        for frame in frames {
-            let width = frame.width();
-            assert!(width == frame.height() && width.is_power_of_two() && width <= 32);
+        for (frame_number, frame) in frames.into_iter().enumerate() {
+            let image = pad_to_sprite_size(frame.image, None, || {
+                format!("Frame {} of {}", frame_number, filename.display())
+            });
 
-            let image = Image::load_from_dyn_image(frame);
-            add_to_optimiser(&mut optimiser, &image, width as usize);
-            images.push(image);
+            collision_masks.push(collision_mask(&image, None));
+            images.push(Image::load_from_dyn_image(image));
+            durations.push(frame.duration.min(u16::MAX as u32) as u16);
         }
     }
 
-    let optimised_results = optimiser.optimise_palettes(None);
+    TokenStream::from(generate_sprites(
+        &images,
+        &durations,
+        &tags,
+        None,
+        with_collision_masks.then(|| &collision_masks[..]),
+        &filenames,
+    ))
+}
+
+#[proc_macro]
+pub fn include_sprite_sheet_inner(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as syn::LitStr);
+
+    let filename = input.value();
+
+    let root = std::env::var("CARGO_MANIFEST_DIR").expect("Failed to get cargo manifest dir");
+    let path = Path::new(&root).join(&*filename);
+    let parent = path
+        .parent()
+        .expect("Expected a parent directory for the path");
+
+    let config = config::parse_sprite_sheet(&path.to_string_lossy());
+
+    let image_path = parent.join(config.filename());
+    let sheet = image::open(&image_path).expect("Expected image to exist");
+
+    let (frame_width, frame_height) = config.frame_size();
+    let frames = split_into_frames(&sheet, frame_width, frame_height).unwrap_or_else(|| {
+        panic!(
+            "Image {} is {}x{}, which is not a multiple of the frame size {}x{}",
+            image_path.display(),
+            sheet.width(),
+            sheet.height(),
+            frame_width,
+            frame_height
+        )
+    });
+
+    let config_tags = config.tags();
+
+    for tag in &config_tags {
+        assert!(tag.start <= tag.end, "Tag {} has start > end", tag.name);
+        assert!(
+            tag.end < frames.len(),
+            "Tag {} ends at frame {}, but {} only has {} frames",
+            tag.name,
+            tag.end,
+            image_path.display(),
+            frames.len()
+        );
+    }
+
+    let durations = frame_durations(frames.len(), config.frame_duration(), &config_tags);
+
+    let tags: Vec<_> = config_tags
+        .into_iter()
+        .map(|tag| SpriteTag {
+            name: tag.name,
+            start: tag.start,
+            end: tag.end,
+            direction: tag.direction,
+        })
+        .collect();
+
+    let transparent_colour = config.transparent_colour();
+
+    let mut images = Vec::new();
+    let mut collision_masks = Vec::new();
+
+    for (frame_number, frame) in frames.into_iter().enumerate() {
+        let image = pad_to_sprite_size(frame, transparent_colour, || {
+            format!("Frame {} of {}", frame_number, image_path.display())
+        });
+
+        collision_masks.push(collision_mask(&image, transparent_colour));
+        images.push(Image::load_from_dyn_image(image));
+    }
+
+    TokenStream::from(generate_sprites(
+        &images,
+        &durations,
+        &tags,
+        transparent_colour,
+        config.collision_masks().then(|| &collision_masks[..]),
+        &[path.clone(), image_path],
+    ))
+}
 
-    let (palette_data, tile_data, assignments) = palete_tile_data(&optimised_results, &images);
+/// How long each frame of a sprite sheet is shown for. A frame can be in more than one
+/// tag, so tags which overlap can't set different durations.
+fn frame_durations(
+    frame_count: usize,
+    default_duration: u16,
+    tags: &[config::SpriteSheetTag],
+) -> Vec<u16> {
+    let mut durations: Vec<Option<(&str, u16)>> = vec![None; frame_count];
+
+    for tag in tags {
+        let duration = match tag.duration {
+            Some(duration) => duration,
+            None => continue,
+        };
+
+        for (frame, frame_duration) in durations[tag.start..=tag.end].iter_mut().enumerate() {
+            match frame_duration {
+                Some((other_tag, other_duration)) if *other_duration != duration => panic!(
+                    "Tags {} and {} both contain frame {}, but have different durations",
+                    other_tag,
+                    tag.name,
+                    tag.start + frame
+                ),
+                _ => *frame_duration = Some((&tag.name, duration)),
+            }
+        }
+    }
+
+    durations
+        .into_iter()
+        .map(|duration| duration.map_or(default_duration, |(_, duration)| duration))
+        .collect()
+}
+
+/// A named range of frames along with the direction that they should be played in.
+struct SpriteTag {
+    name: String,
+    start: usize,
+    end: usize,
+    direction: usize,
+}
+
+/// Splits a sprite sheet in to frames of the given size, going left to right and then
+/// top to bottom. Returns `None` if the sheet isn't a multiple of the frame size.
+fn split_into_frames(
+    sheet: &image::DynamicImage,
+    frame_width: u32,
+    frame_height: u32,
+) -> Option<Vec<image::DynamicImage>> {
+    let (width, height) = sheet.dimensions();
+    if frame_width == 0
+        || frame_height == 0
+        || width % frame_width != 0
+        || height % frame_height != 0
+    {
+        return None;
+    }
+
+    let mut frames = Vec::new();
+    for y in (0..height).step_by(frame_height as usize) {
+        for x in (0..width).step_by(frame_width as usize) {
+            frames.push(sheet.crop_imm(x, y, frame_width, frame_height));
+        }
+    }
+
+    Some(frames)
+}
+
+/// Pads the frame to the smallest hardware sprite size it fits in to. The padding and
+/// any fully transparent pixels are set to the transparent colour, so that they are
+/// drawn as transparent rather than the colour the alpha channel was hiding.
+fn pad_to_sprite_size(
+    frame: image::DynamicImage,
+    transparent_colour: Option<Colour>,
+    description: impl Fn() -> String,
+) -> image::DynamicImage {
+    let (width, height) = frame.dimensions();
+    let (sprite_width, sprite_height) = sprite_size_for(width, height).unwrap_or_else(|| {
+        panic!(
+            "{} is {}x{}, but sprites can be at most 64x64 and must fit in one of the hardware sprite sizes",
+            description(),
+            width,
+            height
+        )
+    });
+
+    let transparent = transparent_colour.map_or(image::Rgba([0, 0, 0, 0]), |colour| {
+        image::Rgba([colour.r, colour.g, colour.b, 0])
+    });
+
+    let mut padded = image::RgbaImage::from_pixel(sprite_width, sprite_height, transparent);
+    image::imageops::replace(&mut padded, &frame, 0, 0);
+
+    for pixel in padded.pixels_mut().filter(|pixel| pixel[3] == 0) {
+        *pixel = transparent;
+    }
+
+    image::DynamicImage::ImageRgba8(padded)
+}
+
+/// Generates a 1bpp mask of the solid pixels in the frame, one `u64` per row with
+/// bit `x` set if the pixel in column `x` is solid. A pixel is solid if it isn't
+/// fully transparent and isn't the transparent colour.
+fn collision_mask(frame: &image::DynamicImage, transparent_colour: Option<Colour>) -> Vec<u64> {
+    let (width, height) = frame.dimensions();
+    assert!(width <= 64, "Collision masks can be at most 64 pixels wide");
+
+    (0..height)
+        .map(|y| {
+            (0..width)
+                .filter(|&x| {
+                    let pixel = frame.get_pixel(x, y);
+                    pixel[3] != 0
+                        && Some(Colour::from_rgb(pixel[0], pixel[1], pixel[2]))
+                            != transparent_colour
+                })
+                .fold(0, |row, x| row | (1 << x))
+        })
+        .collect()
+}
+
+/// Generates the `PALETTES`, `SPRITES`, `FRAME_DURATIONS` and `TAGS` constants which
+/// are used to build a `Graphics` in agb, along with `COLLISION_MASKS` if requested.
+fn generate_sprites(
+    images: &[Image],
+    durations: &[u16],
+    tags: &[SpriteTag],
+    transparent_colour: Option<Colour>,
+    collision_masks: Option<&[Vec<u64>]>,
+    include_paths: &[PathBuf],
+) -> proc_macro2::TokenStream {
+    let mut optimiser = palette16::Palette16Optimiser::new();
+    for image in images {
+        add_to_optimiser(&mut optimiser, image, image.width, image.height);
+    }
+
+    let optimised_results = optimiser.optimise_palettes(transparent_colour);
+
+    let (palette_data, tile_data, assignments) = palete_tile_data(&optimised_results, images);
 
     let palette_data = palette_data.iter().map(|colours| {
         quote! {
@@ -137,45 +389,67 @@ pub fn include_aseprite_inner(input: TokenStream) -> TokenStream {
     let sprites = images
         .iter()
         .zip(assignments.iter())
-        .map(|(f, assignment)| {
+        .enumerate()
+        .map(|(i, (f, assignment))| {
             let start: usize = pre;
             let end: usize = pre + (f.width / 8) * (f.height / 8) * 32;
             let data = ByteString(&tile_data[start..end]);
             pre = end;
             let width = f.width;
             let height = f.height;
+            let collision_mask = collision_masks.map(|_| {
+                quote! {
+                    .with_collision_mask(&COLLISION_MASKS[#i])
+                }
+            });
             quote! {
                 Sprite::new(
                     &PALETTES[#assignment],
                     #data,
                     Size::from_width_height(#width, #height)
-                )
+                )#collision_mask
             }
         });
 
-    let tags = tags.iter().flat_map(|(tag, num_images)| {
-        tag.iter().map(move |tag| {
-            let start = tag.from_frame() as usize + num_images;
-            let end = tag.to_frame() as usize + num_images;
-            let direction = tag.animation_direction() as usize;
-
-            let name = tag.name();
-            assert!(start <= end, "Tag {} has start > end", name);
-
+    let collision_masks = collision_masks.map(|collision_masks| {
+        let masks = collision_masks.iter().zip(images).map(|(rows, image)| {
+            let width = image.width;
+            let height = image.height;
             quote! {
-                (#name, Tag::new(SPRITES, #start, #end, #direction))
+                CollisionMask::new(#width, #height, &[#(#rows),*])
             }
-        })
+        });
+
+        quote! {
+            const COLLISION_MASKS: &[CollisionMask] = &[
+                #(#masks),*
+            ];
+        }
     });
 
-    let include_paths = filenames.iter().map(|s| {
+    let tags = tags.iter().map(|tag| {
+        let SpriteTag {
+            name,
+            start,
+            end,
+            direction,
+        } = tag;
+
+        assert!(start <= end, "Tag {} has start > end", name);
+
+        quote! {
+            (#name, Tag::new(SPRITES, FRAME_DURATIONS, #start, #end, #direction))
+        }
+    });
+
+    let include_paths = include_paths.iter().map(|s| {
         let s = s.as_os_str().to_string_lossy();
         quote! {
             const _: &[u8] = include_bytes!(#s);
         }
     });
 
-    let module = quote! {
+    quote! {
         #(#include_paths)*
 
 
@@ -183,19 +457,23 @@ pub fn include_aseprite_inner(input: TokenStream) -> TokenStream {
             #(#palette_data),*
         ];
 
+        #collision_masks
+
         pub const SPRITES: &[Sprite] = &[
             #(#sprites),*
         ];
 
+        const FRAME_DURATIONS: &[u16] = &[
+            #(#durations),*
+        ];
+
         const TAGS: &TagMap = &TagMap::new(
             &[
                 #(#tags),*
             ]
         );
 
-    };
-
-    TokenStream::from(module)
+    }
 }
 
 fn convert_image(
@@ -227,25 +505,26 @@ fn convert_image(
 
 fn optimiser_for_image(image: &Image, tile_size: usize) -> palette16::Palette16Optimiser {
     let mut palette_optimiser = palette16::Palette16Optimiser::new();
-    add_to_optimiser(&mut palette_optimiser, image, tile_size);
+    add_to_optimiser(&mut palette_optimiser, image, tile_size, tile_size);
     palette_optimiser
 }
 
 fn add_to_optimiser(
     palette_optimiser: &mut palette16::Palette16Optimiser,
     image: &Image,
-    tile_size: usize,
+    tile_width: usize,
+    tile_height: usize,
 ) {
-    let tiles_x = image.width / tile_size;
-    let tiles_y = image.height / tile_size;
+    let tiles_x = image.width / tile_width;
+    let tiles_y = image.height / tile_height;
 
     for y in 0..tiles_y {
         for x in 0..tiles_x {
             let mut palette = palette16::Palette16::new();
 
-            for j in 0..tile_size {
-                for i in 0..tile_size {
-                    let colour = image.colour(x * tile_size + i, y * tile_size + j);
+            for j in 0..tile_height {
+                for i in 0..tile_width {
+                    let colour = image.colour(x * tile_width + i, y * tile_height + j);
 
                     palette.add_colour(colour);
                 }
@@ -277,24 +556,16 @@ fn palete_tile_data(
 
     let mut tile_data = Vec::new();
 
-    for image in images {
-        let tile_size = image.height;
-        let tiles_x = image.width / tile_size;
-        let tiles_y = image.height / tile_size;
-
-        for y in 0..tiles_y {
-            for x in 0..tiles_x {
-                let palette_index = optimiser.assignments[y * tiles_x + x];
-                let palette = &optimiser.optimised_palettes[palette_index];
-
-                for inner_y in 0..tile_size / 8 {
-                    for inner_x in 0..tile_size / 8 {
-                        for j in inner_y * 8..inner_y * 8 + 8 {
-                            for i in inner_x * 8..inner_x * 8 + 8 {
-                                let colour = image.colour(x * tile_size + i, y * tile_size + j);
-                                tile_data.push(palette.colour_index(colour));
-                            }
-                        }
+    // Each image is a single sprite with its own palette, and the tiles of a
+    // sprite are stored left to right, top to bottom for 1D object mapping.
+    for (image, &palette_index) in images.iter().zip(optimiser.assignments.iter()) {
+        let palette = &optimiser.optimised_palettes[palette_index];
+
+        for y in 0..image.height / 8 {
+            for x in 0..image.width / 8 {
+                for j in y * 8..y * 8 + 8 {
+                    for i in x * 8..x * 8 + 8 {
+                        tile_data.push(palette.colour_index(image.colour(i, j)));
                     }
                 }
             }
@@ -311,6 +582,30 @@ fn palete_tile_data(
     (palette_data, tile_data, assignments)
 }
 
+/// The sizes of sprite supported by the hardware, smallest first.
+const SPRITE_SIZES: &[(u32, u32)] = &[
+    (8, 8),
+    (16, 8),
+    (8, 16),
+    (16, 16),
+    (32, 8),
+    (8, 32),
+    (32, 16),
+    (16, 32),
+    (32, 32),
+    (64, 32),
+    (32, 64),
+    (64, 64),
+];
+
+/// Finds the smallest hardware sprite size which a frame of the given size fits in to.
+fn sprite_size_for(width: u32, height: u32) -> Option<(u32, u32)> {
+    SPRITE_SIZES
+        .iter()
+        .copied()
+        .find(|&(sprite_width, sprite_height)| width <= sprite_width && height <= sprite_height)
+}
+
 fn flatten_group(expr: &Expr) -> &Expr {
     match expr {
         Expr::Group(group) => &group.expr,
@@ -320,15 +615,32 @@ fn flatten_group(expr: &Expr) -> &Expr {
 
 #[proc_macro]
 pub fn include_font(input: TokenStream) -> TokenStream {
-    let parser = Punctuated::<Expr, syn::Token![,]>::parse_separated_nonempty;
-    let parsed = match parser.parse(input) {
+    let parser = |input: ParseStream| {
+        let anti_aliased = if input.peek(syn::Ident) && input.peek2(syn::Token![;]) {
+            let option: syn::Ident = input.parse()?;
+            if option != "anti_aliased" {
+                return Err(syn::Error::new(option.span(), "Expected `anti_aliased`"));
+            }
+            input.parse::<syn::Token![;]>()?;
+            true
+        } else {
+            false
+        };
+
+        let args = Punctuated::<Expr, syn::Token![,]>::parse_separated_nonempty(input)?;
+        Ok((anti_aliased, args))
+    };
+    let (anti_aliased, parsed) = match parser.parse(input) {
         Ok(e) => e,
         Err(e) => return e.to_compile_error().into(),
     };
 
     let all_args: Vec<_> = parsed.into_iter().collect();
-    if all_args.len() != 2 {
-        panic!("Include_font requires 2 arguments, got {}", all_args.len());
+    if all_args.len() != 2 && all_args.len() != 3 {
+        panic!(
+            "Include_font requires 2 or 3 arguments, got {}",
+            all_args.len()
+        );
     }
 
     let filename = match flatten_group(&all_args[0]) {
@@ -353,12 +665,26 @@ pub fn include_font(input: TokenStream) -> TokenStream {
         _ => panic!("Expected literal float or integer as second argument to include_font"),
     };
 
+    let character_sets = match all_args.get(2).map(flatten_group) {
+        None => "ascii".to_string(),
+        Some(Expr::Lit(ExprLit {
+            lit: Lit::Str(str_lit),
+            ..
+        })) => str_lit.value(),
+        Some(_) => panic!("Expected literal string as third argument to include_font"),
+    };
+
+    let characters = match font_loader::parse_character_sets(&character_sets) {
+        Ok(characters) => characters,
+        Err(e) => panic!("{}", e),
+    };
+
     let root = std::env::var("CARGO_MANIFEST_DIR").expect("Failed to get cargo manifest dir");
     let path = Path::new(&root).join(&*filename);
 
     let file_content = std::fs::read(&path).expect("Failed to read ttf file");
 
-    let rendered = font_loader::load_font(&file_content, font_size);
+    let rendered = font_loader::load_font(&file_content, font_size, &characters, anti_aliased);
 
     let include_path = path.to_string_lossy();
 
@@ -370,9 +696,68 @@ pub fn include_font(input: TokenStream) -> TokenStream {
     .into()
 }
 
+#[proc_macro]
+pub fn include_bitmap_font(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as syn::LitStr);
+
+    let filename = input.value();
+
+    let root = std::env::var("CARGO_MANIFEST_DIR").expect("Failed to get cargo manifest dir");
+    let path = Path::new(&root).join(&*filename);
+    let parent = path
+        .parent()
+        .expect("Expected a parent directory for the path");
+
+    let config = config::parse_bitmap_font(&path.to_string_lossy());
+
+    let image_path = parent.join(config.filename());
+    let sheet = image::open(&image_path).expect("Expected image to exist");
+
+    let settings = font_loader::BitmapFontSettings {
+        glyph_size: config.glyph_size(),
+        characters: config.characters(),
+        transparent_colour: config.transparent_colour(),
+        marker_colour: config.marker_colour(),
+        baseline: config.baseline(),
+        line_height: config.line_height(),
+        letter_spacing: config.letter_spacing(),
+    };
+
+    let rendered = font_loader::load_bitmap_font(&sheet, &settings);
+
+    let config_path = path.to_string_lossy();
+    let image_path = image_path.to_string_lossy();
+
+    quote!({
+        let _ = include_bytes!(#config_path);
+        let _ = include_bytes!(#image_path);
+
+        #rendered
+    })
+    .into()
+}
+
 #[cfg(test)]
 mod tests {
     use asefile::AnimationDirection;
+    use image::GenericImageView;
+
+    use super::{
+        collision_mask, frame_durations, pad_to_sprite_size, split_into_frames, sprite_size_for,
+    };
+    use crate::colour::Colour;
+    use crate::config::SpriteSheetTag;
+    use crate::image_loader::Image;
+
+    fn tag(name: &str, start: usize, end: usize, duration: Option<u16>) -> SpriteSheetTag {
+        SpriteSheetTag {
+            name: name.to_owned(),
+            start,
+            end,
+            direction: 0,
+            duration,
+        }
+    }
 
     #[test]
     // These directions defined in agb and have these values. This is important
@@ -383,4 +768,99 @@ mod tests {
         assert_eq!(AnimationDirection::Reverse as usize, 1);
         assert_eq!(AnimationDirection::PingPong as usize, 2);
     }
+
+    #[test]
+    fn frames_are_padded_to_the_nearest_sprite_size() {
+        assert_eq!(sprite_size_for(16, 16), Some((16, 16)));
+        assert_eq!(sprite_size_for(16, 32), Some((16, 32)));
+        assert_eq!(sprite_size_for(32, 8), Some((32, 8)));
+        assert_eq!(sprite_size_for(64, 64), Some((64, 64)));
+        assert_eq!(sprite_size_for(12, 7), Some((16, 8)));
+        assert_eq!(sprite_size_for(20, 20), Some((32, 32)));
+        assert_eq!(sprite_size_for(48, 20), Some((64, 32)));
+        assert_eq!(sprite_size_for(65, 8), None);
+        assert_eq!(sprite_size_for(8, 128), None);
+    }
+
+    #[test]
+    fn sprite_sheets_are_split_left_to_right_then_top_to_bottom() {
+        let mut sheet = image::RgbaImage::new(32, 16);
+        for (x, y, pixel) in sheet.enumerate_pixels_mut() {
+            *pixel = image::Rgba([(x / 16) as u8, (y / 8) as u8, 0, 255]);
+        }
+        let sheet = image::DynamicImage::ImageRgba8(sheet);
+
+        let frames = split_into_frames(&sheet, 16, 8).unwrap();
+        let first_pixels: Vec<_> = frames
+            .iter()
+            .map(|frame| {
+                assert_eq!(frame.dimensions(), (16, 8));
+                let pixel = frame.get_pixel(0, 0);
+                (pixel[0], pixel[1])
+            })
+            .collect();
+
+        assert_eq!(first_pixels, [(0, 0), (1, 0), (0, 1), (1, 1)]);
+
+        assert!(split_into_frames(&sheet, 24, 8).is_none());
+        assert!(split_into_frames(&sheet, 0, 8).is_none());
+    }
+
+    #[test]
+    fn frame_durations_come_from_the_tags() {
+        let tags = [
+            tag("idle", 0, 1, Some(50)),
+            tag("walk", 1, 2, Some(50)),
+            tag("blink", 2, 3, None),
+            tag("jump", 4, 4, Some(80)),
+        ];
+
+        assert_eq!(frame_durations(6, 100, &tags), [50, 50, 50, 100, 80, 100]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Tags idle and walk both contain frame 1")]
+    fn overlapping_tags_must_have_the_same_duration() {
+        let tags = [tag("idle", 0, 1, Some(50)), tag("walk", 1, 2, Some(80))];
+
+        frame_durations(3, 100, &tags);
+    }
+
+    #[test]
+    fn frames_are_padded_with_the_transparent_colour() {
+        let mut frame = image::RgbaImage::from_pixel(12, 10, image::Rgba([255, 255, 255, 255]));
+        frame.put_pixel(1, 1, image::Rgba([0, 0, 0, 0]));
+        let frame = image::DynamicImage::ImageRgba8(frame);
+
+        let transparent_colour = Colour::from_rgb(255, 0, 255);
+        let padded = pad_to_sprite_size(frame, Some(transparent_colour), String::new);
+        assert_eq!(padded.dimensions(), (16, 16));
+
+        let image = Image::load_from_dyn_image(padded.clone());
+        assert_eq!(image.colour(0, 0), Colour::from_rgb(255, 255, 255));
+        assert_eq!(image.colour(1, 1), transparent_colour);
+        assert_eq!(image.colour(12, 0), transparent_colour);
+        assert_eq!(image.colour(0, 10), transparent_colour);
+
+        let mask = collision_mask(&padded, Some(transparent_colour));
+        assert_eq!(mask[0], 0b1111_1111_1111);
+        assert_eq!(mask[1], 0b1111_1111_1101);
+        assert_eq!(mask[10], 0);
+    }
+
+    #[test]
+    fn collision_masks_ignore_transparent_pixels() {
+        let mut frame = image::RgbaImage::new(8, 2);
+        frame.put_pixel(0, 0, image::Rgba([255, 255, 255, 255]));
+        frame.put_pixel(7, 0, image::Rgba([255, 255, 255, 255]));
+        frame.put_pixel(3, 1, image::Rgba([255, 0, 255, 255]));
+        frame.put_pixel(4, 1, image::Rgba([0, 0, 0, 255]));
+        let frame = image::DynamicImage::ImageRgba8(frame);
+
+        assert_eq!(collision_mask(&frame, None), [0b1000_0001, 0b0001_1000]);
+        assert_eq!(
+            collision_mask(&frame, Some(Colour::from_rgb(255, 0, 255))),
+            [0b1000_0001, 0b0001_0000]
+        );
+    }
 }
@@ -47,6 +47,8 @@ InterruptHandler:
     strh r1, [r2] @ acknowlege bios requests
 
     bx lr @ return to bios
+    .global agb_rs__interrupt_handler_end
+agb_rs__interrupt_handler_end:
 .pool
 
 
@@ -0,0 +1,94 @@
+use core::arch::asm;
+use core::fmt::Write;
+use core::ops::Range;
+
+use crate::mgba::{DebugLevel, Mgba};
+
+/// Each address takes up 11 bytes of the 255 which can be logged at once.
+const MAX_FRAMES: usize = 20;
+
+/// The stack is always in iwram, so a frame pointer outside of it means the
+/// end of the chain has been reached or the frame pointer wasn't set up.
+const STACK: Range<usize> = 0x0300_0000..0x0300_8000;
+
+/// Calls `f` with the return address of each frame on the stack, starting with
+/// the caller of this function. This relies on the code being compiled with
+/// `-Cforce-frame-pointers=yes` so that `r7` points to a record of the previous
+/// frame pointer followed by the return address. The walk stops at the first
+/// frame without such a record, for example in code compiled for ARM rather
+/// than thumb.
+#[inline(never)]
+pub(crate) fn walk(mut f: impl FnMut(usize)) {
+    let mut frame_pointer: usize;
+    unsafe {
+        asm!("movs {}, r7", out(reg) frame_pointer);
+    }
+
+    for _ in 0..MAX_FRAMES {
+        if !STACK.contains(&frame_pointer) || frame_pointer % 4 != 0 {
+            return;
+        }
+
+        let record = frame_pointer as *const usize;
+        let (previous_frame_pointer, return_address) =
+            unsafe { (record.read_volatile(), record.add(1).read_volatile()) };
+
+        if return_address == 0 {
+            return;
+        }
+
+        f(return_address);
+
+        // the stack grows downwards, so the caller's frame is always higher up
+        if previous_frame_pointer <= frame_pointer {
+            return;
+        }
+
+        frame_pointer = previous_frame_pointer;
+    }
+}
+
+/// Logs the return addresses on the stack in a format which the mgba test
+/// runner turns into function names, files and lines using the ELF's debug
+/// information.
+pub(crate) fn log(mgba: &mut Mgba) {
+    let _ = write!(mgba, "Backtrace:");
+    walk(|address| {
+        let _ = write!(mgba, " {:#010x}", address);
+    });
+    mgba.set_level(DebugLevel::Error);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[inline(never)]
+    fn nested_walk(depth: u32, f: &mut dyn FnMut(usize)) {
+        if depth == 0 {
+            walk(f);
+        } else {
+            nested_walk(depth - 1, f);
+            // stops the recursion being turned into a loop
+            core::hint::black_box(depth);
+        }
+    }
+
+    #[test_case]
+    fn backtrace_walks_the_stack(_gba: &mut crate::Gba) {
+        let mut addresses = [0; MAX_FRAMES];
+        let mut count = 0;
+        nested_walk(3, &mut |address| {
+            addresses[count] = address;
+            count += 1;
+        });
+
+        assert!(count > 4, "only found {} frames", count);
+        // the recursive calls all return to the same place
+        assert_eq!(addresses[1], addresses[2]);
+        assert!(addresses[..count]
+            .iter()
+            .all(|&address| (0x0800_0000..0x0a00_0000).contains(&address)
+                || (0x0300_0000..0x0300_8000).contains(&address)));
+    }
+}
@@ -0,0 +1,339 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use bare_metal::Mutex;
+use core::cell::RefCell;
+use core::fmt::Write;
+
+use crate::display::video::Video;
+use crate::logger::Level;
+use crate::memory_mapped::MemoryMapped1DArray;
+
+/// Where the crash log starts in SRAM, which is the 1KiB just before the part
+/// used by the logger's SRAM target.
+pub(crate) const SRAM_CRASH_LOG_OFFSET: usize = 0x6C00;
+const SRAM_CRASH_LOG: MemoryMapped1DArray<u8, 0x400> =
+    unsafe { MemoryMapped1DArray::new(0x0E00_0000 + SRAM_CRASH_LOG_OFFSET) };
+
+const MAGIC: [u8; 4] = *b"agbC";
+/// The magic followed by the little endian lengths of the message and the log
+const HEADER_LENGTH: usize = 8;
+const MESSAGE_LENGTH: usize = 0x200 - HEADER_LENGTH;
+const LOG_LENGTH: usize = 0x200;
+
+/// How many of the most recent log lines are shown on the last crash screen
+const LINES_SHOWN: usize = 6;
+
+/// The most recent log lines, kept in memory until there is a crash so that
+/// SRAM is only written to once.
+struct RecentLog {
+    bytes: [u8; LOG_LENGTH],
+    position: usize,
+    is_full: bool,
+}
+
+impl RecentLog {
+    const fn new() -> Self {
+        Self {
+            bytes: [0; LOG_LENGTH],
+            position: 0,
+            is_full: false,
+        }
+    }
+
+    fn contents(&self) -> impl Iterator<Item = u8> + '_ {
+        let (oldest, newest) = if self.is_full {
+            (&self.bytes[self.position..], &self.bytes[..self.position])
+        } else {
+            (&self.bytes[..self.position], &[][..])
+        };
+
+        // the oldest line has been partly overwritten if the buffer is full
+        let mut is_partial_line = self.is_full;
+        oldest
+            .iter()
+            .chain(newest)
+            .copied()
+            .skip_while(move |&byte| {
+                let skip = is_partial_line;
+                is_partial_line &= byte != b'\n';
+                skip
+            })
+    }
+}
+
+impl Write for RecentLog {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        for byte in s.bytes() {
+            self.bytes[self.position] = byte;
+            self.position += 1;
+
+            if self.position == LOG_LENGTH {
+                self.position = 0;
+                self.is_full = true;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+static RECENT_LOG: Mutex<RefCell<Option<RecentLog>>> = Mutex::new(RefCell::new(None));
+
+/// Starts keeping the most recent lines logged with [`error!`][crate::error],
+/// [`warn!`][crate::warn], [`info!`][crate::info], [`debug!`][crate::debug]
+/// and the `log` crate, so that they can be saved along with the panic message
+/// if the game crashes. The crash log is written to 1KiB of SRAM starting at
+/// offset `0x6C00`, which [`SaveManager`][crate::save::SaveManager] won't use
+/// for save data while this is enabled. This also declares that the game uses
+/// SRAM, so that it is saved by emulators and flash cartridges.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// if let Some(crash) = agb::crash_log::last_crash() {
+///     crash.show(&mut gba.display.video);
+///     agb::crash_log::clear();
+///     wait_for_button_press();
+/// }
+///
+/// agb::crash_log::enable();
+/// ```
+pub fn enable() {
+    crate::save::use_sram();
+
+    crate::interrupt::free(|key| {
+        RECENT_LOG
+            .borrow(*key)
+            .borrow_mut()
+            .get_or_insert_with(RecentLog::new);
+    });
+}
+
+/// Stops keeping recent log lines and saving crashes to SRAM.
+pub fn disable() {
+    crate::interrupt::free(|key| {
+        RECENT_LOG.borrow(*key).replace(None);
+    });
+}
+
+pub fn is_enabled() -> bool {
+    crate::interrupt::free(|key| RECENT_LOG.borrow(*key).borrow().is_some())
+}
+
+/// Called by the logger with every message, to keep the most recent ones if
+/// the crash log is enabled.
+pub(crate) fn remember(level: Level, output: core::fmt::Arguments) {
+    crate::interrupt::free(|key| {
+        if let Some(recent_log) = RECENT_LOG.borrow(*key).borrow_mut().as_mut() {
+            let _ = writeln!(recent_log, "{} {}", level, output);
+        }
+    });
+}
+
+/// Writes bytes to part of the crash log in SRAM, dropping any which don't fit.
+struct SramWriter {
+    start: usize,
+    capacity: usize,
+    length: usize,
+}
+
+impl SramWriter {
+    fn new(start: usize, capacity: usize) -> Self {
+        Self {
+            start,
+            capacity,
+            length: 0,
+        }
+    }
+
+    fn write_byte(&mut self, byte: u8) {
+        if self.length < self.capacity {
+            SRAM_CRASH_LOG.set(self.start + self.length, byte);
+            self.length += 1;
+        }
+    }
+}
+
+impl Write for SramWriter {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        s.bytes().for_each(|byte| self.write_byte(byte));
+        Ok(())
+    }
+}
+
+fn read_u16(offset: usize) -> usize {
+    SRAM_CRASH_LOG.get(offset) as usize | ((SRAM_CRASH_LOG.get(offset + 1) as usize) << 8)
+}
+
+fn write_u16(offset: usize, value: usize) {
+    SRAM_CRASH_LOG.set(offset, value as u8);
+    SRAM_CRASH_LOG.set(offset + 1, (value >> 8) as u8);
+}
+
+/// Called by the panic handler to save the panic message and recent log lines
+/// to SRAM if the crash log is enabled. This doesn't allocate, so works even if
+/// the panic was caused by running out of memory.
+pub(crate) fn save(message: core::fmt::Arguments) {
+    crate::interrupt::free(|key| {
+        // the panic could have happened while the recent log was being written
+        let recent_log = match RECENT_LOG.borrow(*key).try_borrow() {
+            Ok(recent_log) => recent_log,
+            Err(_) => return,
+        };
+        let recent_log = match recent_log.as_ref() {
+            Some(recent_log) => recent_log,
+            None => return,
+        };
+
+        // a crash log which was only partly written shouldn't be read back
+        SRAM_CRASH_LOG.set(0, 0);
+
+        let mut message_writer = SramWriter::new(HEADER_LENGTH, MESSAGE_LENGTH);
+        let _ = message_writer.write_fmt(message);
+
+        let mut log_writer = SramWriter::new(HEADER_LENGTH + MESSAGE_LENGTH, LOG_LENGTH);
+        recent_log
+            .contents()
+            .for_each(|byte| log_writer.write_byte(byte));
+
+        write_u16(4, message_writer.length);
+        write_u16(6, log_writer.length);
+        for (i, &byte) in MAGIC.iter().enumerate().rev() {
+            SRAM_CRASH_LOG.set(i, byte);
+        }
+    });
+}
+
+/// The panic message and recent log lines saved when the game crashed.
+pub struct Crash {
+    message: String,
+    log: String,
+}
+
+impl Crash {
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The lines logged just before the crash, oldest first.
+    pub fn log(&self) -> &str {
+        &self.log
+    }
+
+    /// Shows the panic message and the last few log lines on the screen using
+    /// bitmap mode 3.
+    pub fn show(&self, video: &mut Video) {
+        let log = self.log.trim_end();
+        let recent_lines_start = log
+            .rmatch_indices('\n')
+            .nth(LINES_SHOWN - 1)
+            .map_or(0, |(i, _)| i + 1);
+
+        crate::panic_screen::draw(
+            video.bitmap3(),
+            format_args!(
+                "The game crashed last time!\n\n{}\n\n{}",
+                self.message,
+                &log[recent_lines_start..]
+            ),
+        );
+    }
+}
+
+/// The crash saved to SRAM, if there has been one since it was last cleared.
+pub fn last_crash() -> Option<Crash> {
+    crate::interrupt::free(|_| {
+        let has_magic = MAGIC
+            .iter()
+            .enumerate()
+            .all(|(i, &byte)| SRAM_CRASH_LOG.get(i) == byte);
+
+        let message_length = read_u16(4);
+        let log_length = read_u16(6);
+
+        if !has_magic || message_length > MESSAGE_LENGTH || log_length > LOG_LENGTH {
+            return None;
+        }
+
+        let read = |start: usize, length: usize| {
+            let bytes: Vec<u8> = (start..start + length)
+                .map(|i| SRAM_CRASH_LOG.get(i))
+                .collect();
+            String::from_utf8_lossy(&bytes).into_owned()
+        };
+
+        Some(Crash {
+            message: read(HEADER_LENGTH, message_length),
+            log: read(HEADER_LENGTH + MESSAGE_LENGTH, log_length),
+        })
+    })
+}
+
+/// Forgets the crash saved to SRAM, so it won't be shown again.
+pub fn clear() {
+    crate::interrupt::free(|_| SRAM_CRASH_LOG.set(0, 0));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logger::{self, Targets};
+    use alloc::format;
+
+    #[test_case]
+    fn crash_log_saves_message_and_recent_log(_gba: &mut crate::Gba) {
+        clear();
+        save(format_args!("not enabled"));
+        assert!(last_crash().is_none());
+
+        let previous_targets = logger::targets();
+        logger::set_targets(Targets::empty());
+
+        enable();
+        let level = 1;
+        crate::info!("loading level {}", level);
+        crate::warn!("running out of memory");
+
+        let line = 42;
+        save(format_args!("panicked at src/main.rs:{}", line));
+
+        let crash = last_crash().unwrap();
+        assert_eq!(crash.message(), "panicked at src/main.rs:42");
+        assert_eq!(
+            crash.log(),
+            "INFO loading level 1\nWARN running out of memory\n"
+        );
+
+        clear();
+        assert!(last_crash().is_none());
+        disable();
+        logger::set_targets(previous_targets);
+    }
+
+    #[test_case]
+    fn crash_log_keeps_whole_lines(gba: &mut crate::Gba) {
+        let previous_targets = logger::targets();
+        logger::set_targets(Targets::empty());
+
+        enable();
+        for i in 0..LOG_LENGTH {
+            crate::debug!("line {}", i);
+        }
+
+        let long_message = "a".repeat(2 * MESSAGE_LENGTH);
+        save(format_args!("{}", long_message));
+
+        let crash = last_crash().unwrap();
+        assert_eq!(crash.message().len(), MESSAGE_LENGTH);
+        assert!(crash.log().starts_with("DEBUG line "));
+        assert!(crash
+            .log()
+            .ends_with(&format!("DEBUG line {}\n", LOG_LENGTH - 1)));
+
+        crash.show(&mut gba.display.video);
+
+        clear();
+        disable();
+        logger::set_targets(previous_targets);
+    }
+}
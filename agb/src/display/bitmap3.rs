@@ -26,4 +26,12 @@ impl Bitmap3 {
         let y = y.try_into().unwrap();
         BITMAP_MODE_3.set(x, y, colour)
     }
+
+    /// Reads the colour of the point at (x, y) coordinates and panics if (x, y)
+    /// is out of the bounds of the screen.
+    pub fn read_point(&self, x: i32, y: i32) -> u16 {
+        let x = x.try_into().unwrap();
+        let y = y.try_into().unwrap();
+        BITMAP_MODE_3.get(x, y)
+    }
 }
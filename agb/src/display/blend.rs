@@ -0,0 +1,116 @@
+use bitflags::bitflags;
+
+use crate::memory_mapped::MemoryMapped;
+
+const BLEND_CONTROL: MemoryMapped<u16> = unsafe { MemoryMapped::new(0x0400_0050) };
+const BLEND_ALPHA: MemoryMapped<u16> = unsafe { MemoryMapped::new(0x0400_0052) };
+const BLEND_BRIGHTNESS: MemoryMapped<u16> = unsafe { MemoryMapped::new(0x0400_0054) };
+
+bitflags! {
+    /// The layers which can take part in colour special effects.
+    pub struct BlendLayers: u16 {
+        const BG0 = 1 << 0;
+        const BG1 = 1 << 1;
+        const BG2 = 1 << 2;
+        const BG3 = 1 << 3;
+        const OBJECT = 1 << 4;
+        const BACKDROP = 1 << 5;
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlendMode {
+    /// No special effects, apart from semi-transparent objects which are always
+    /// alpha blended.
+    Off = 0,
+    /// Blends the first target layers with the second target layers beneath them.
+    Alpha = 1,
+    /// Fades the first target layers towards white.
+    Brighten = 2,
+    /// Fades the first target layers towards black.
+    Darken = 3,
+}
+
+/// Controls the colour special effects registers. Semi-transparent objects
+/// (see [`GraphicsMode::SemiTransparent`]) are always blended with the second
+/// target layers using the alpha values set here, whatever the blend mode.
+///
+/// [`GraphicsMode::SemiTransparent`]: super::object::GraphicsMode::SemiTransparent
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// // make semi-transparent objects 50% see-through over background 0
+/// gba.display
+///     .blend
+///     .set_second_targets(BlendLayers::BG0)
+///     .set_alpha(8, 8);
+/// ```
+#[non_exhaustive]
+pub struct Blend {}
+
+impl Blend {
+    pub(crate) const unsafe fn new() -> Self {
+        Blend {}
+    }
+
+    pub fn set_mode(&mut self, mode: BlendMode) -> &mut Self {
+        BLEND_CONTROL.set_bits(mode as u16, 2, 6);
+        self
+    }
+
+    /// Sets the layers which are affected by the blend mode.
+    pub fn set_first_targets(&mut self, layers: BlendLayers) -> &mut Self {
+        BLEND_CONTROL.set_bits(layers.bits(), 6, 0);
+        self
+    }
+
+    /// Sets the layers which are blended with when using alpha blending or
+    /// semi-transparent objects.
+    pub fn set_second_targets(&mut self, layers: BlendLayers) -> &mut Self {
+        BLEND_CONTROL.set_bits(layers.bits(), 6, 8);
+        self
+    }
+
+    /// Sets the weights of the first and second targets when alpha blending, in
+    /// sixteenths. Values larger than 16 are treated as 16.
+    pub fn set_alpha(&mut self, first: u8, second: u8) -> &mut Self {
+        let first = first.min(16) as u16;
+        let second = second.min(16) as u16;
+        BLEND_ALPHA.set(first | (second << 8));
+        self
+    }
+
+    /// Sets how far towards white or black to fade when brightening or
+    /// darkening, in sixteenths. Values larger than 16 are treated as 16.
+    pub fn set_fade(&mut self, amount: u8) -> &mut Self {
+        BLEND_BRIGHTNESS.set(amount.min(16) as u16);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn blend_sets_control_register(gba: &mut crate::Gba) {
+        gba.display
+            .blend
+            .set_mode(BlendMode::Alpha)
+            .set_first_targets(BlendLayers::OBJECT)
+            .set_second_targets(BlendLayers::BG0 | BlendLayers::BACKDROP)
+            .set_alpha(10, 20);
+
+        assert_eq!(BLEND_CONTROL.get(), 0b0010_0001_0101_0000);
+        assert_eq!(BLEND_ALPHA.get(), 0x100a);
+
+        gba.display
+            .blend
+            .set_mode(BlendMode::Off)
+            .set_first_targets(BlendLayers::empty())
+            .set_second_targets(BlendLayers::empty());
+
+        assert_eq!(BLEND_CONTROL.get(), 0);
+    }
+}
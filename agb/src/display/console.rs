@@ -0,0 +1,314 @@
+use alloc::string::String;
+use alloc::{vec, vec::Vec};
+use bare_metal::Mutex;
+use core::cell::RefCell;
+
+use super::tile_data::TileData;
+use super::tiled::{
+    MapLoan, RegularBackgroundSize, RegularMap, TileFormat, TileSet, TileSetting, Tiled0,
+    VRamManager, TRANSPARENT_TILE_INDEX,
+};
+use super::Priority;
+
+crate::include_gfx!("gfx/debug_console.toml");
+
+/// The console's font, which is also used for the panic screen. The tiles are
+/// the printable ASCII characters starting at space.
+pub(crate) const FONT: TileData = debug_console::font;
+
+/// The number of characters which fit on a line of the screen
+const COLUMNS: usize = 30;
+/// The number of lines visible on the screen at once
+const VISIBLE_ROWS: usize = 20;
+/// The number of lines in the background, which is used as a ring buffer
+const ROWS: usize = 32;
+
+/// The console uses the last background palette so it is unlikely to clash
+/// with the game's palettes.
+const PALETTE: u8 = 15;
+
+/// The most text which will be kept from [`println!`][crate::println] between
+/// commits of the console capturing it.
+const MAX_CAPTURED_LENGTH: usize = COLUMNS * VISIBLE_ROWS;
+
+static CAPTURED: Mutex<RefCell<Option<String>>> = Mutex::new(RefCell::new(None));
+
+/// Called by [`println!`][crate::println] when not running in mgba, to send the
+/// output to a console capturing it if there is one.
+#[doc(hidden)]
+pub fn print(output: core::fmt::Arguments) {
+    crate::interrupt::free(|key| {
+        if let Some(captured) = CAPTURED.borrow(*key).borrow_mut().as_mut() {
+            use core::fmt::Write;
+
+            if captured.len() < MAX_CAPTURED_LENGTH {
+                let _ = writeln!(captured, "{}", output);
+            }
+        }
+    });
+}
+
+/// Shows text on a background using a built in 8x8 font, for debugging on
+/// hardware or emulators where mgba's logging isn't available. Text is written
+/// using [`core::fmt::Write`] and wraps at the edge of the screen, with the
+/// console scrolling up once the screen is full.
+///
+/// The console uses the last background palette and takes up one of the
+/// regular backgrounds for as long as it exists.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use core::fmt::Write;
+///
+/// let (gfx, mut vram) = gba.display.video.tiled0();
+/// let mut console = DebugConsole::new(&gfx, &mut vram);
+/// console.capture_println();
+///
+/// loop {
+///     input.update();
+///     if input.is_just_pressed(Button::SELECT) {
+///         console.toggle();
+///     }
+///
+///     writeln!(console, "frame {}", frame).unwrap();
+///
+///     vblank.wait_for_vblank();
+///     console.commit(&mut vram);
+/// }
+/// ```
+pub struct DebugConsole<'a> {
+    map: MapLoan<'a, RegularMap>,
+    text: Vec<u8>,
+    dirty_rows: u32,
+
+    line: usize,
+    column: usize,
+    visible: bool,
+}
+
+impl<'a> DebugConsole<'a> {
+    /// Reserves a background for the console and shows it above the other
+    /// backgrounds.
+    pub fn new(gfx: &'a Tiled0, vram: &mut VRamManager) -> Self {
+        let mut map = gfx.background(Priority::P0, RegularBackgroundSize::Background32x32);
+
+        vram.set_background_palette(PALETTE, &FONT.palettes[0]);
+        map.show();
+
+        Self {
+            map,
+            text: vec![b' '; COLUMNS * ROWS],
+            dirty_rows: u32::MAX,
+
+            line: 0,
+            column: 0,
+            visible: true,
+        }
+    }
+
+    /// Sends the output of [`println!`][crate::println] to this console when not
+    /// running in mgba, until the console is dropped. The output appears the
+    /// next time the console is committed.
+    pub fn capture_println(&mut self) {
+        crate::interrupt::free(|key| {
+            CAPTURED
+                .borrow(*key)
+                .borrow_mut()
+                .get_or_insert_with(String::new);
+        });
+    }
+
+    /// Removes all the text from the console.
+    pub fn clear(&mut self) {
+        self.text.fill(b' ');
+        self.dirty_rows = u32::MAX;
+        self.line = 0;
+        self.column = 0;
+    }
+
+    pub fn show(&mut self) {
+        self.visible = true;
+        self.map.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.map.hide();
+    }
+
+    /// Shows the console if it is hidden, and hides it if it is shown.
+    pub fn toggle(&mut self) {
+        if self.visible {
+            self.hide();
+        } else {
+            self.show();
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    fn row(&self) -> usize {
+        self.line % ROWS
+    }
+
+    fn new_line(&mut self) {
+        self.line += 1;
+        self.column = 0;
+
+        let row = self.row();
+        self.text[row * COLUMNS..(row + 1) * COLUMNS].fill(b' ');
+        self.dirty_rows |= 1 << row;
+    }
+
+    fn write_char(&mut self, c: char) {
+        if c == '\n' {
+            self.new_line();
+            return;
+        }
+
+        if self.column == COLUMNS {
+            self.new_line();
+        }
+
+        let character = match c {
+            ' '..='~' => c as u8,
+            _ => b'?',
+        };
+
+        let row = self.row();
+        self.text[row * COLUMNS + self.column] = character;
+        self.dirty_rows |= 1 << row;
+        self.column += 1;
+    }
+
+    /// Updates the background with the text written since the last commit and
+    /// scrolls it so the newest line is at the bottom of the screen. This
+    /// should be called during vblank.
+    pub fn commit(&mut self, vram: &mut VRamManager) {
+        let captured = crate::interrupt::free(|key| {
+            CAPTURED
+                .borrow(*key)
+                .borrow_mut()
+                .as_mut()
+                .map(core::mem::take)
+        });
+        for c in captured.iter().flat_map(|captured| captured.chars()) {
+            self.write_char(c);
+        }
+
+        let tiles = TileSet::new(FONT.tiles, TileFormat::FourBpp);
+
+        let dirty_rows = self.dirty_rows;
+        for row in (0..ROWS).filter(|row| dirty_rows & (1 << row) != 0) {
+            for column in 0..COLUMNS {
+                let tile_id = match self.text[row * COLUMNS + column] {
+                    b' ' => TRANSPARENT_TILE_INDEX,
+                    character => (character - b' ') as u16,
+                };
+
+                self.map.set_tile(
+                    vram,
+                    (column as u16, row as u16).into(),
+                    &tiles,
+                    TileSetting::new(tile_id, false, false, PALETTE),
+                );
+            }
+        }
+        self.dirty_rows = 0;
+
+        let scroll = (self.line + 1).saturating_sub(VISIBLE_ROWS) % ROWS * 8;
+        self.map.set_scroll_pos((0, scroll as u16).into());
+        self.map.commit(vram);
+    }
+}
+
+impl core::fmt::Write for DebugConsole<'_> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        for c in s.chars() {
+            self.write_char(c);
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for DebugConsole<'_> {
+    fn drop(&mut self) {
+        crate::interrupt::free(|key| {
+            CAPTURED.borrow(*key).replace(None);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+    use core::fmt::Write;
+
+    fn line<'a>(console: &'a DebugConsole, line: usize) -> &'a str {
+        let row = line % ROWS;
+        core::str::from_utf8(&console.text[row * COLUMNS..(row + 1) * COLUMNS])
+            .unwrap()
+            .trim_end()
+    }
+
+    #[test_case]
+    fn debug_console_wraps_and_scrolls(gba: &mut crate::Gba) {
+        let (gfx, mut vram) = gba.display.video.tiled0();
+        let mut console = DebugConsole::new(&gfx, &mut vram);
+
+        let name = "world";
+        writeln!(console, "hello, {}", name).unwrap();
+        write!(console, "{}é", "a".repeat(COLUMNS)).unwrap();
+        console.commit(&mut vram);
+
+        assert_eq!(line(&console, 0), "hello, world");
+        assert_eq!(line(&console, 1), "a".repeat(COLUMNS));
+        assert_eq!(line(&console, 2), "?");
+        assert_eq!(console.map.scroll_pos(), (0u16, 0u16).into());
+
+        for i in 0..ROWS {
+            writeln!(console, "{}", i).unwrap();
+        }
+        console.commit(&mut vram);
+
+        assert_eq!(line(&console, console.line - 1), (ROWS - 1).to_string());
+        assert_eq!(
+            console.map.scroll_pos(),
+            (0, ((console.line + 1 - VISIBLE_ROWS) % ROWS * 8) as u16).into()
+        );
+
+        console.toggle();
+        assert!(!console.is_visible());
+        console.toggle();
+        assert!(console.is_visible());
+    }
+
+    #[test_case]
+    fn debug_console_captures_println(gba: &mut crate::Gba) {
+        let (gfx, mut vram) = gba.display.video.tiled0();
+
+        {
+            let mut console = DebugConsole::new(&gfx, &mut vram);
+            console.capture_println();
+
+            let number = 1;
+            print(format_args!("captured {}", number));
+            console.commit(&mut vram);
+
+            assert_eq!(line(&console, 0), "captured 1");
+            assert_eq!(console.line, 1);
+        }
+
+        // once the console is dropped, nothing is captured any more
+        print(format_args!("lost"));
+        assert!(crate::interrupt::free(|key| CAPTURED
+            .borrow(*key)
+            .borrow()
+            .is_none()));
+    }
+}
@@ -0,0 +1,251 @@
+use alloc::vec::Vec;
+use core::fmt::Write;
+use core::ops::Range;
+
+use super::{Font, TextRenderer};
+use crate::fixnum::Vector2D;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextAlignment {
+    Left,
+    Centre,
+    Right,
+}
+
+struct Line {
+    /// The range of the line in the text, excluding trailing spaces
+    range: Range<usize>,
+    width: i32,
+}
+
+/// Text which has been word wrapped to fit in a box and split into pages if
+/// it doesn't fit in the box vertically.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// let layout = TextLayout::new(&FONT, text, (200, 40).into(), TextAlignment::Centre);
+///
+/// for page in 0..layout.page_count() {
+///     let mut writer = FONT.render_text((1u16, 15u16).into(), 1, 0, &mut bg, &mut vram);
+///     layout.render_page(page, &mut writer);
+///     writer.commit();
+///
+///     wait_for_button_press();
+/// }
+/// ```
+pub struct TextLayout<'a> {
+    font: &'a Font,
+    text: &'a str,
+    size: Vector2D<i32>,
+    alignment: TextAlignment,
+    lines: Vec<Line>,
+    lines_per_page: usize,
+}
+
+impl<'a> TextLayout<'a> {
+    /// Lays out the text in a box of the given size in pixels. Lines are broken
+    /// at spaces and `\n`, and words which are wider than the box are broken
+    /// wherever they need to be.
+    pub fn new(
+        font: &'a Font,
+        text: &'a str,
+        size: Vector2D<i32>,
+        alignment: TextAlignment,
+    ) -> Self {
+        let mut layout = Self {
+            font,
+            text,
+            size,
+            alignment,
+            lines: Vec::new(),
+            lines_per_page: (size.y / font.line_height()).max(1) as usize,
+        };
+
+        let mut paragraph_start = 0;
+        for paragraph in text.split('\n') {
+            layout.wrap_paragraph(paragraph_start, paragraph);
+            paragraph_start += paragraph.len() + 1;
+        }
+
+        layout
+    }
+
+    fn wrap_paragraph(&mut self, paragraph_start: usize, paragraph: &str) {
+        let font = self.font;
+        let max_width = self.size.x;
+        let width = |range: Range<usize>| font.line_width(&paragraph[range]);
+
+        let mut lines = Vec::new();
+        let mut line_start = 0;
+        let mut line_end = 0;
+        let mut word_start = 0;
+
+        for word in paragraph.split(' ') {
+            let word_end = word_start + word.len();
+
+            if !word.is_empty() {
+                if width(line_start..word_end) > max_width {
+                    if line_end > line_start {
+                        lines.push(line_start..line_end);
+                        line_start = word_start;
+                    }
+
+                    // the word doesn't fit on a line by itself, so break it
+                    // after as many characters as fit
+                    while width(line_start..word_end) > max_width {
+                        let split = paragraph[line_start..word_end]
+                            .char_indices()
+                            .skip(1)
+                            .map(|(i, _)| line_start + i)
+                            .take_while(|&end| width(line_start..end) <= max_width)
+                            .last()
+                            .unwrap_or_else(|| {
+                                line_start
+                                    + paragraph[line_start..].chars().next().unwrap().len_utf8()
+                            });
+
+                        lines.push(line_start..split);
+                        line_start = split;
+                    }
+                }
+
+                line_end = word_end;
+            }
+
+            word_start = word_end + 1;
+        }
+
+        lines.push(line_start..line_end);
+
+        self.lines.extend(lines.into_iter().map(|range| Line {
+            width: width(range.clone()),
+            range: (range.start + paragraph_start)..(range.end + paragraph_start),
+        }));
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.lines.len().div_ceil(self.lines_per_page)
+    }
+
+    /// The lines on the given page, which is empty if the page is past the end
+    /// of the text.
+    fn page_lines(&self, page: usize) -> &[Line] {
+        let start = page
+            .saturating_mul(self.lines_per_page)
+            .min(self.lines.len());
+        let end = start
+            .saturating_add(self.lines_per_page)
+            .min(self.lines.len());
+        &self.lines[start..end]
+    }
+
+    /// The size of the text on the given page, which is at most the size of the
+    /// box the text was laid out in unless a single character is wider than it.
+    /// Pages past the end of the text are empty.
+    pub fn page_size(&self, page: usize) -> Vector2D<i32> {
+        let lines = self.page_lines(page);
+        let width = lines.iter().map(|line| line.width).max().unwrap_or(0);
+        (width, lines.len() as i32 * self.font.line_height()).into()
+    }
+
+    /// The size of the largest page of text.
+    pub fn size(&self) -> Vector2D<i32> {
+        let width = self.lines.iter().map(|line| line.width).max().unwrap_or(0);
+        let lines = self.lines.len().min(self.lines_per_page) as i32;
+        (width, lines * self.font.line_height()).into()
+    }
+
+    /// The range in the text and the position of each line on the given page.
+    pub(super) fn line_positions(
+        &self,
+        page: usize,
+    ) -> impl Iterator<Item = (Range<usize>, Vector2D<i32>)> + '_ {
+        self.page_lines(page)
+            .iter()
+            .enumerate()
+            .map(move |(i, line)| {
+                let x = match self.alignment {
+                    TextAlignment::Left => 0,
+                    TextAlignment::Centre => (self.size.x - line.width) / 2,
+                    TextAlignment::Right => self.size.x - line.width,
+                };
+
+                (
+                    line.range.clone(),
+                    (x, i as i32 * self.font.line_height()).into(),
+                )
+            })
+    }
+
+    /// Renders the given page with the top left of the box at the top left of
+    /// the renderer. The renderer should use the same font as the layout. Pages
+    /// past the end of the text render nothing.
+    pub fn render_page(&self, page: usize, renderer: &mut TextRenderer) {
+        for (range, position) in self.line_positions(page) {
+            renderer.set_cursor(position);
+            renderer
+                .write_str(&self.text[range])
+                .expect("Writing to a text renderer can't fail");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FONT: Font = crate::include_font!("examples/font/yoster.ttf", 12);
+
+    fn lines<'a>(layout: &'a TextLayout) -> Vec<&'a str> {
+        layout
+            .lines
+            .iter()
+            .map(|line| &layout.text[line.range.clone()])
+            .collect()
+    }
+
+    #[test_case]
+    fn text_layout_wraps_words(_gba: &mut crate::Gba) {
+        let width = FONT.line_width("the quick");
+        let layout = TextLayout::new(
+            &FONT,
+            "the quick brown fox\n\njumps",
+            (width, 100).into(),
+            TextAlignment::Left,
+        );
+
+        assert_eq!(lines(&layout), ["the quick", "brown fox", "", "jumps"]);
+        assert!(layout.lines.iter().all(|line| line.width <= width));
+        assert_eq!(layout.size().x, width);
+    }
+
+    #[test_case]
+    fn text_layout_breaks_long_words(_gba: &mut crate::Gba) {
+        let width = FONT.line_width("aaa");
+        let layout = TextLayout::new(&FONT, "aaaaaaa", (width, 100).into(), TextAlignment::Left);
+
+        assert_eq!(lines(&layout), ["aaa", "aaa", "a"]);
+    }
+
+    #[test_case]
+    fn text_layout_splits_into_pages(_gba: &mut crate::Gba) {
+        let layout = TextLayout::new(
+            &FONT,
+            "one\ntwo\nthree\nfour\nfive",
+            (100, FONT.line_height() * 2).into(),
+            TextAlignment::Right,
+        );
+
+        assert_eq!(layout.page_count(), 3);
+        assert_eq!(
+            layout.page_size(2),
+            (FONT.line_width("five"), FONT.line_height()).into()
+        );
+        assert_eq!(layout.size().y, FONT.line_height() * 2);
+
+        assert_eq!(layout.page_size(3), (0, 0).into());
+        assert_eq!(layout.page_size(usize::MAX), (0, 0).into());
+        assert_eq!(layout.line_positions(3).count(), 0);
+    }
+}
@@ -0,0 +1,625 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{Error, Write};
+
+use crate::fixnum::Vector2D;
+use crate::hash_map::HashMap;
+
+use super::tiled::{DynamicTile, RegularMap, TileSetting, VRamManager};
+
+mod layout;
+mod object_text;
+mod typewriter;
+
+pub use layout::{TextAlignment, TextLayout};
+pub use object_text::ObjectText;
+pub use typewriter::Typewriter;
+
+pub struct FontLetter {
+    character: char,
+    width: u8,
+    height: u8,
+    data: &'static [u8],
+    xmin: i8,
+    ymin: i8,
+    advance_width: u8,
+    kerning: &'static [(char, i8)],
+}
+
+impl FontLetter {
+    pub const fn new(
+        character: char,
+        width: u8,
+        height: u8,
+        data: &'static [u8],
+        xmin: i8,
+        ymin: i8,
+        advance_width: u8,
+    ) -> Self {
+        Self {
+            character,
+            width,
+            height,
+            data,
+            xmin,
+            ymin,
+            advance_width,
+            kerning: &[],
+        }
+    }
+
+    /// Sets the adjustments to the advance width when this letter is followed by
+    /// the given letters. These must be sorted by the following letter.
+    pub const fn with_kerning(self, kerning: &'static [(char, i8)]) -> Self {
+        Self { kerning, ..self }
+    }
+
+    /// The coverage of the pixel from 0 (empty) to 3 (solid). Letters with
+    /// levels of coverage are stored with 2 bits per pixel, and other letters
+    /// with 1 bit per pixel which is set if the pixel is solid.
+    fn coverage(&self, x: i32, y: i32, has_coverage_levels: bool) -> u8 {
+        let pos = (x + y * self.width as i32) as usize;
+
+        if has_coverage_levels {
+            (self.data[pos / 4] >> ((pos & 3) * 2)) & 3
+        } else if (self.data[pos / 8] >> (pos & 7)) & 1 != 0 {
+            3
+        } else {
+            0
+        }
+    }
+
+    fn kerning(&self, next: char) -> i32 {
+        self.kerning
+            .binary_search_by_key(&next, |&(c, _)| c)
+            .map_or(0, |i| self.kerning[i].1 as i32)
+    }
+}
+
+/// A font imported using [`include_font!`][crate::include_font]. Letters are
+/// sorted by character so that they can be found with a binary search.
+/// Characters which aren't in the font are displayed as `?`.
+pub struct Font {
+    letters: &'static [FontLetter],
+    line_height: i32,
+    ascent: i32,
+    has_coverage_levels: bool,
+}
+
+impl Font {
+    pub const fn new(letters: &'static [FontLetter], line_height: i32, ascent: i32) -> Self {
+        Self {
+            letters,
+            line_height,
+            ascent,
+            has_coverage_levels: false,
+        }
+    }
+
+    /// Marks the letters as being stored with 2 bits per pixel, giving the 4
+    /// levels of coverage used for anti-aliasing.
+    pub const fn with_coverage_levels(self) -> Self {
+        Self {
+            has_coverage_levels: true,
+            ..self
+        }
+    }
+
+    fn try_letter(&self, letter: char) -> Option<&'static FontLetter> {
+        self.letters
+            .binary_search_by_key(&letter, |l| l.character)
+            .ok()
+            .map(|i| &self.letters[i])
+    }
+
+    fn letter(&self, letter: char) -> &'static FontLetter {
+        self.try_letter(letter)
+            .or_else(|| self.try_letter('?'))
+            .unwrap_or(&self.letters[0])
+    }
+
+    /// Whether the font contains the given character.
+    pub fn contains(&self, letter: char) -> bool {
+        self.try_letter(letter).is_some()
+    }
+
+    pub fn line_height(&self) -> i32 {
+        self.line_height
+    }
+
+    /// The width and height in pixels of the given text without any wrapping.
+    pub fn measure(&self, text: &str) -> Vector2D<i32> {
+        let lines = text.split('\n');
+        let line_count = lines.clone().count() as i32;
+        let width = lines.map(|line| self.line_width(line)).max().unwrap_or(0);
+
+        (width, line_count * self.line_height).into()
+    }
+
+    /// The width of a single line of text, which must not contain `\n`.
+    fn line_width(&self, line: &str) -> i32 {
+        let mut width = 0;
+        let mut previous_letter: Option<&FontLetter> = None;
+
+        for c in line.chars() {
+            let letter = self.letter(c);
+            if let Some(previous_letter) = previous_letter {
+                width += previous_letter.kerning(letter.character);
+            }
+
+            width += letter.advance_width as i32;
+            previous_letter = Some(letter);
+        }
+
+        width
+    }
+
+    /// The adjustment to the position of `right` when it follows `left`.
+    pub fn kerning(&self, left: char, right: char) -> i32 {
+        self.try_letter(left)
+            .map_or(0, |letter| letter.kerning(right))
+    }
+}
+
+impl Font {
+    /// Renders text onto the background starting at the given tile. Without
+    /// any of the effects set on the [`TextRenderer`], the foreground colour
+    /// is ORed over the background colour, so for example foreground 1 over
+    /// background 2 gives colour 3. Anti-aliasing, outlines and shadows replace
+    /// pixels with their own colours instead.
+    pub fn render_text<'a>(
+        &'a self,
+        tile_pos: Vector2D<u16>,
+        foreground_colour: u8,
+        background_colour: u8,
+        bg: &'a mut RegularMap,
+        vram_manager: &'a mut VRamManager,
+    ) -> TextRenderer<'a> {
+        TextRenderer {
+            style: LetterStyle::new(foreground_colour, background_colour),
+            previous_letter: None,
+            current_x_pos: 0,
+            current_y_pos: 0,
+            font: self,
+            tile_pos,
+            vram_manager,
+            bg,
+            tiles: Default::default(),
+            new_tiles: Vec::new(),
+        }
+    }
+}
+
+/// Marks pixels in the letter canvas which haven't been drawn to.
+const EMPTY: u8 = 0xff;
+/// Marks outline and shadow pixels in the letter canvas, which don't draw over
+/// letters which have already been rendered.
+const DECORATION: u8 = 0x10;
+
+/// The colours and effects used to draw letters, which are shared between
+/// rendering text to backgrounds and to objects.
+#[derive(Clone, Copy)]
+struct LetterStyle {
+    foreground_colour: u8,
+    background_colour: u8,
+    anti_alias_colours: Option<[u8; 2]>,
+    outline_colour: Option<u8>,
+    shadow_colour: Option<u8>,
+}
+
+/// A letter drawn along with its outline and shadow, ready to be copied into
+/// tiles.
+struct LetterCanvas {
+    pixels: Vec<u8>,
+    /// The position of the top left of the canvas relative to the top left of
+    /// the text
+    position: Vector2D<i32>,
+    width: i32,
+    height: i32,
+}
+
+impl LetterCanvas {
+    /// The pixel at the given position relative to the top left of the text.
+    fn pixel(&self, x: i32, y: i32) -> u8 {
+        let x = x - self.position.x;
+        let y = y - self.position.y;
+
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            EMPTY
+        } else {
+            self.pixels[(x + y * self.width) as usize]
+        }
+    }
+}
+
+impl LetterStyle {
+    fn new(foreground_colour: u8, background_colour: u8) -> Self {
+        Self {
+            foreground_colour,
+            background_colour,
+            anti_alias_colours: None,
+            outline_colour: None,
+            shadow_colour: None,
+        }
+    }
+
+    /// Whether the letters are drawn without anti-aliasing, outlines or
+    /// shadows.
+    fn is_plain(&self) -> bool {
+        self.anti_alias_colours.is_none()
+            && self.outline_colour.is_none()
+            && self.shadow_colour.is_none()
+    }
+
+    /// Draws a pixel from a letter canvas into a row of a 4bpp tile, where `x`
+    /// is the position of the pixel in the row.
+    ///
+    /// Plain letters are ORed over what is already there, so the foreground and
+    /// background colours combine in the same way they always have. With any
+    /// effects, each pixel is replaced instead, and outlines and shadows are
+    /// only drawn over the background.
+    fn draw_pixel(&self, line: &mut u32, x: i32, pixel: u8) {
+        if pixel == EMPTY {
+            return;
+        }
+
+        let shift = (x & 7) * 4;
+        let colour = ((pixel & 0xf) as u32) << shift;
+
+        if self.is_plain() {
+            *line |= colour;
+            return;
+        }
+
+        if pixel & DECORATION != 0 && (*line >> shift) & 0xf != self.background_colour as u32 {
+            return;
+        }
+
+        *line = (*line & !(0xf << shift)) | colour;
+    }
+
+    fn letter_colour(&self, coverage: u8) -> Option<u8> {
+        match (coverage, self.anti_alias_colours) {
+            (0, _) => None,
+            (3, _) => Some(self.foreground_colour),
+            (coverage, Some(colours)) => Some(colours[coverage as usize - 1]),
+            (2, None) => Some(self.foreground_colour),
+            (_, None) => None,
+        }
+    }
+
+    /// Draws the letter with the cursor at the given position.
+    fn draw(&self, font: &Font, letter: &FontLetter, cursor: Vector2D<i32>) -> LetterCanvas {
+        let margin = self.outline_colour.is_some() as i32;
+        let shadow = self.shadow_colour.is_some() as i32;
+
+        let width = letter.width as i32 + 2 * margin + shadow;
+        let height = letter.height as i32 + 2 * margin + shadow;
+
+        let mut pixels = vec![EMPTY; (width * height) as usize];
+        let index = |x: i32, y: i32| (x + y * width) as usize;
+        let is_letter = |pixel: u8| pixel & DECORATION == 0;
+
+        for y in 0..letter.height as i32 {
+            for x in 0..letter.width as i32 {
+                let coverage = letter.coverage(x, y, font.has_coverage_levels);
+                if let Some(colour) = self.letter_colour(coverage) {
+                    pixels[index(x + margin, y + margin)] = colour;
+                }
+            }
+        }
+
+        if let Some(outline_colour) = self.outline_colour {
+            for y in margin..(height - margin - shadow) {
+                for x in margin..(width - margin - shadow) {
+                    if !is_letter(pixels[index(x, y)]) {
+                        continue;
+                    }
+
+                    for (dx, dy) in (-1..=1).flat_map(|dy| (-1..=1).map(move |dx| (dx, dy))) {
+                        let pixel = &mut pixels[index(x + dx, y + dy)];
+                        if *pixel == EMPTY {
+                            *pixel = outline_colour | DECORATION;
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some(shadow_colour) = self.shadow_colour {
+            // going backwards means the shadow never casts its own shadow
+            for y in (0..height - 1).rev() {
+                for x in (0..width - 1).rev() {
+                    if pixels[index(x, y)] != EMPTY && pixels[index(x + 1, y + 1)] == EMPTY {
+                        pixels[index(x + 1, y + 1)] = shadow_colour | DECORATION;
+                    }
+                }
+            }
+        }
+
+        let x = (cursor.x + letter.xmin as i32).max(0) - margin;
+        let y = cursor.y + font.ascent - letter.height as i32 - letter.ymin as i32 - margin;
+
+        LetterCanvas {
+            pixels,
+            position: (x, y).into(),
+            width,
+            height,
+        }
+    }
+}
+
+pub struct TextRenderer<'a> {
+    style: LetterStyle,
+    previous_letter: Option<&'a FontLetter>,
+    current_x_pos: i32,
+    current_y_pos: i32,
+    font: &'a Font,
+    tile_pos: Vector2D<u16>,
+    vram_manager: &'a mut VRamManager,
+    bg: &'a mut RegularMap,
+    tiles: HashMap<(i32, i32), DynamicTile<'a>>,
+    /// Tiles which haven't been placed on the background by [`TextRenderer::update`]
+    new_tiles: Vec<(i32, i32)>,
+}
+
+impl<'a> Write for TextRenderer<'a> {
+    fn write_str(&mut self, text: &str) -> Result<(), Error> {
+        for c in text.chars() {
+            if c == '\n' {
+                self.current_y_pos += self.font.line_height;
+                self.current_x_pos = 0;
+                self.previous_letter = None;
+                continue;
+            }
+
+            let letter = self.font.letter(c);
+
+            if let Some(previous_letter) = self.previous_letter {
+                self.current_x_pos += previous_letter.kerning(letter.character);
+            }
+
+            self.render_letter(letter);
+
+            self.current_x_pos += letter.advance_width as i32;
+            self.previous_letter = Some(letter);
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> TextRenderer<'a> {
+    /// Moves where the next letter is drawn to the given position in pixels
+    /// relative to the top left of the text.
+    pub fn set_cursor(&mut self, position: Vector2D<i32>) {
+        self.current_x_pos = position.x;
+        self.current_y_pos = position.y;
+        self.previous_letter = None;
+    }
+
+    /// Sets the colour of letters which are written after this.
+    pub fn set_foreground_colour(&mut self, colour: u8) {
+        self.style.foreground_colour = colour;
+    }
+
+    /// Draws the edges of letters using the given palette indices rather than
+    /// the foreground colour. These should be a ramp from the background colour
+    /// to the foreground colour, with the first being closest to the background.
+    /// Only fonts included with `anti_aliased;` have edges to draw, see
+    /// [`include_font!`][crate::include_font].
+    #[must_use]
+    pub fn with_anti_aliasing(mut self, colours: [u8; 2]) -> Self {
+        self.style.anti_alias_colours = Some(colours);
+        self
+    }
+
+    /// Draws a 1 pixel outline around each letter in the given colour.
+    #[must_use]
+    pub fn with_outline(mut self, colour: u8) -> Self {
+        self.style.outline_colour = Some(colour);
+        self
+    }
+
+    /// Draws a drop shadow 1 pixel below and to the right of each letter in the
+    /// given colour.
+    #[must_use]
+    pub fn with_shadow(mut self, colour: u8) -> Self {
+        self.style.shadow_colour = Some(colour);
+        self
+    }
+
+    fn render_letter(&mut self, letter: &FontLetter) {
+        let cursor = (self.current_x_pos, self.current_y_pos).into();
+        let canvas = self.style.draw(self.font, letter, cursor);
+
+        let vram_manager = &mut self.vram_manager;
+        let new_tiles = &mut self.new_tiles;
+        let style = self.style;
+        let background_colour = style.background_colour;
+
+        let x_start = canvas.position.x;
+        let y_start = canvas.position.y;
+
+        let x_tiles = (x_start.div_euclid(8).max(0))..=((x_start + canvas.width - 1).div_euclid(8));
+        let y_tiles =
+            (y_start.div_euclid(8).max(0))..=((y_start + canvas.height - 1).div_euclid(8));
+
+        for tile_y in y_tiles {
+            for tile_x in x_tiles.clone() {
+                let y_range =
+                    (tile_y * 8).max(y_start)..((tile_y + 1) * 8).min(y_start + canvas.height);
+                let x_range =
+                    (tile_x * 8).max(x_start)..((tile_x + 1) * 8).min(x_start + canvas.width);
+
+                for y in y_range {
+                    for x in x_range.clone() {
+                        let pixel = canvas.pixel(x, y);
+                        if pixel == EMPTY {
+                            continue;
+                        }
+
+                        let tile = self.tiles.entry((tile_x, tile_y)).or_insert_with(|| {
+                            new_tiles.push((tile_x, tile_y));
+                            vram_manager.new_dynamic_tile().fill_with(background_colour)
+                        });
+
+                        style.draw_pixel(&mut tile.tile_data[(y & 7) as usize], x, pixel);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Places the tiles which have been created since the last update on the
+    /// background, so that text can be shown while it is still being written.
+    /// Tiles which have already been placed show any further changes straight
+    /// away. The background still needs committing for new tiles to appear.
+    pub fn update(&mut self) {
+        for (x, y) in self.new_tiles.drain(..) {
+            let tile = self.tiles.get(&(x, y)).unwrap();
+            self.bg.set_tile(
+                self.vram_manager,
+                (self.tile_pos.x + x as u16, self.tile_pos.y + y as u16).into(),
+                &tile.tile_set(),
+                TileSetting::from_raw(tile.tile_index()),
+            );
+        }
+    }
+
+    pub fn commit(mut self) {
+        let tiles = core::mem::take(&mut self.tiles);
+
+        for ((x, y), tile) in tiles.into_iter() {
+            self.bg.set_tile(
+                self.vram_manager,
+                (self.tile_pos.x + x as u16, self.tile_pos.y + y as u16).into(),
+                &tile.tile_set(),
+                TileSetting::from_raw(tile.tile_index()),
+            );
+            self.vram_manager.remove_dynamic_tile(tile);
+        }
+    }
+}
+
+impl<'a> Drop for TextRenderer<'a> {
+    fn drop(&mut self) {
+        let tiles = core::mem::take(&mut self.tiles);
+
+        for (_, tile) in tiles.into_iter() {
+            self.vram_manager.remove_dynamic_tile(tile);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    const FONT: Font = crate::include_font!("examples/font/yoster.ttf", 12);
+
+    #[test_case]
+    fn font_display(gba: &mut crate::Gba) {
+        let (gfx, mut vram) = gba.display.video.tiled0();
+
+        let mut bg = gfx.background(
+            crate::display::Priority::P0,
+            crate::display::tiled::RegularBackgroundSize::Background32x32,
+        );
+
+        vram.set_background_palette_raw(&[
+            0x0000, 0x0ff0, 0x00ff, 0xf00f, 0xf0f0, 0x0f0f, 0xaaaa, 0x5555, 0x0000, 0x0000, 0x0000,
+            0x0000, 0x0000, 0x0000, 0x0000, 0x0000,
+        ]);
+
+        let background_tile = vram.new_dynamic_tile().fill_with(0);
+
+        for y in 0..20u16 {
+            for x in 0..30u16 {
+                bg.set_tile(
+                    &mut vram,
+                    (x, y).into(),
+                    &background_tile.tile_set(),
+                    TileSetting::from_raw(background_tile.tile_index()),
+                );
+            }
+        }
+
+        vram.remove_dynamic_tile(background_tile);
+
+        let mut writer = FONT.render_text((0u16, 3u16).into(), 1, 2, &mut bg, &mut vram);
+
+        writeln!(&mut writer, "Hello, World!").unwrap();
+        writeln!(&mut writer, "This is a font rendering example").unwrap();
+
+        writer.commit();
+
+        bg.commit(&mut vram);
+        bg.show();
+
+        crate::test_runner::assert_image_output("examples/font/font-test-output.png");
+    }
+
+    #[test_case]
+    fn font_includes_requested_character_sets(_gba: &mut crate::Gba) {
+        const LATIN1_FONT: Font = crate::include_font!("examples/font/yoster.ttf", 12, "latin1");
+
+        assert!(FONT.contains('A'));
+        assert!(!FONT.contains('é'));
+        assert!(core::ptr::eq(FONT.letter('é'), FONT.letter('?')));
+
+        assert!(LATIN1_FONT.contains('é'));
+        assert!(LATIN1_FONT.contains('ß'));
+        assert!(LATIN1_FONT
+            .letters
+            .windows(2)
+            .all(|pair| pair[0].character < pair[1].character));
+    }
+
+    #[test_case]
+    fn font_outline_and_shadow(gba: &mut crate::Gba) {
+        // at this size, the letters have pixels at every level of coverage
+        const ANTI_ALIASED_FONT: Font =
+            crate::include_font!(anti_aliased; "examples/font/yoster.ttf", 14);
+
+        let (gfx, mut vram) = gba.display.video.tiled0();
+
+        let mut bg = gfx.background(
+            crate::display::Priority::P0,
+            crate::display::tiled::RegularBackgroundSize::Background32x32,
+        );
+
+        let mut writer = ANTI_ALIASED_FONT
+            .render_text((0u16, 0u16).into(), 1, 0, &mut bg, &mut vram)
+            .with_anti_aliasing([2, 3])
+            .with_outline(4)
+            .with_shadow(5);
+
+        write!(&mut writer, "Hi").unwrap();
+
+        let colours_used = writer
+            .tiles
+            .values()
+            .flat_map(|tile| tile.tile_data.iter())
+            .flat_map(|line| (0..8).map(move |i| (line >> (i * 4)) & 0xf))
+            .fold(0u16, |used, colour| used | (1 << colour));
+
+        // the background, foreground, both anti-aliasing colours, the outline
+        // and the shadow, and nothing else
+        assert_eq!(colours_used, 0b111111);
+
+        writer.commit();
+    }
+
+    #[test_case]
+    fn bitmap_fonts_use_marker_widths(_gba: &mut crate::Gba) {
+        const BITMAP_FONT: Font = crate::include_bitmap_font!("gfx/test_bitmap_font.toml");
+
+        assert!(BITMAP_FONT.contains('A'));
+        assert!(!BITMAP_FONT.contains('a'));
+
+        // glyphs are 3 pixels wide plus 1 pixel of spacing, and lines are 1
+        // pixel taller than the glyphs
+        assert_eq!(BITMAP_FONT.measure("AB"), (8, 7).into());
+        assert_eq!(BITMAP_FONT.measure(" !\n!"), (5, 14).into());
+    }
+}
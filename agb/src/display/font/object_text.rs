@@ -0,0 +1,260 @@
+use alloc::vec::Vec;
+
+use super::{Font, FontLetter, LetterStyle, EMPTY};
+use crate::display::object::{DynamicSprite, Object, ObjectController, Size};
+use crate::display::palette16::Palette16;
+use crate::display::Priority;
+use crate::fixnum::Vector2D;
+
+/// Text drawn into sprites and displayed using objects, so that it moves
+/// independently of the backgrounds. This is useful for scores or floating
+/// damage numbers. The background of the text is transparent.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// let mut score = ObjectText::new(&FONT, &object_controller, &PALETTE, 1).with_outline(2);
+/// score.set_text("Score: 100");
+/// score.set_position((8, 8).into()).show();
+///
+/// loop {
+///     // ...
+///     score.set_text(&format!("Score: {}", points));
+///     object_controller.commit();
+/// }
+/// ```
+pub struct ObjectText<'a> {
+    font: &'a Font,
+    controller: &'a ObjectController,
+    palette: &'static Palette16,
+    style: LetterStyle,
+    sprite_size: Size,
+    columns: usize,
+    sprites: Vec<(DynamicSprite<'a>, Object<'a>)>,
+
+    position: Vector2D<i32>,
+    visible: bool,
+    priority: Priority,
+    z: i32,
+}
+
+impl<'a> ObjectText<'a> {
+    /// Creates empty text which is drawn with the given palette. Use
+    /// [`ObjectText::set_text`] to draw the text.
+    pub fn new(
+        font: &'a Font,
+        controller: &'a ObjectController,
+        palette: &'static Palette16,
+        foreground_colour: u8,
+    ) -> Self {
+        // leave space for an outline and shadow
+        let sprite_size = match font.line_height + 3 {
+            0..=8 => Size::S32x8,
+            9..=16 => Size::S32x16,
+            17..=32 => Size::S32x32,
+            _ => Size::S64x64,
+        };
+
+        Self {
+            font,
+            controller,
+            palette,
+            style: LetterStyle::new(foreground_colour, 0),
+            sprite_size,
+            columns: 0,
+            sprites: Vec::new(),
+
+            position: (0, 0).into(),
+            visible: false,
+            priority: Priority::P0,
+            z: 0,
+        }
+    }
+
+    /// See [`TextRenderer::with_anti_aliasing`][super::TextRenderer::with_anti_aliasing].
+    #[must_use]
+    pub fn with_anti_aliasing(mut self, colours: [u8; 2]) -> Self {
+        self.style.anti_alias_colours = Some(colours);
+        self
+    }
+
+    /// Draws a 1 pixel outline around each letter in the given colour.
+    #[must_use]
+    pub fn with_outline(mut self, colour: u8) -> Self {
+        self.style.outline_colour = Some(colour);
+        self
+    }
+
+    /// Draws a drop shadow 1 pixel below and to the right of each letter in the
+    /// given colour.
+    #[must_use]
+    pub fn with_shadow(mut self, colour: u8) -> Self {
+        self.style.shadow_colour = Some(colour);
+        self
+    }
+
+    /// Draws the text, replacing any text which was there before. Objects are
+    /// only allocated or freed if the size of the text changes enough to need
+    /// more or fewer sprites.
+    pub fn set_text(&mut self, text: &str) {
+        let (sprite_width, sprite_height) = self.sprite_size.to_width_height();
+        let (sprite_width, sprite_height) = (sprite_width as i32, sprite_height as i32);
+
+        let margin = self.style.outline_colour.is_some() as i32;
+        let padding = 2 * margin + self.style.shadow_colour.is_some() as i32;
+
+        let size = self.font.measure(text) + (padding, padding).into();
+        let columns = ((size.x + sprite_width - 1) / sprite_width).max(1) as usize;
+        let rows = ((size.y + sprite_height - 1) / sprite_height).max(1) as usize;
+
+        self.columns = columns;
+        self.sprites.truncate(columns * rows);
+        while self.sprites.len() < columns * rows {
+            let sprite = self
+                .controller
+                .dynamic_sprite(self.sprite_size, self.palette);
+            let object = self.controller.object(sprite.sprite());
+            self.sprites.push((sprite, object));
+        }
+
+        for (sprite, _) in self.sprites.iter_mut() {
+            sprite.clear(0);
+        }
+
+        let mut cursor: Vector2D<i32> = (margin, margin).into();
+        let mut previous_letter: Option<&FontLetter> = None;
+
+        for c in text.chars() {
+            if c == '\n' {
+                cursor = (margin, cursor.y + self.font.line_height).into();
+                previous_letter = None;
+                continue;
+            }
+
+            let letter = self.font.letter(c);
+            if let Some(previous_letter) = previous_letter {
+                cursor.x += previous_letter.kerning(letter.character);
+            }
+
+            let canvas = self.style.draw(self.font, letter, cursor);
+            for y in canvas.position.y..(canvas.position.y + canvas.height) {
+                for x in canvas.position.x..(canvas.position.x + canvas.width) {
+                    let pixel = canvas.pixel(x, y);
+                    if pixel == EMPTY || x < 0 || y < 0 || x >= size.x || y >= size.y {
+                        continue;
+                    }
+
+                    let index =
+                        (y / sprite_height) as usize * columns + (x / sprite_width) as usize;
+                    let (x_in_sprite, y_in_sprite) = (x % sprite_width, y % sprite_height);
+                    let tile = (y_in_sprite / 8) * (sprite_width / 8) + x_in_sprite / 8;
+
+                    let tile_data = self.sprites[index].0.tile_data();
+                    self.style.draw_pixel(
+                        &mut tile_data[(tile * 8 + y_in_sprite % 8) as usize],
+                        x,
+                        pixel,
+                    );
+                }
+            }
+
+            cursor.x += letter.advance_width as i32;
+            previous_letter = Some(letter);
+        }
+
+        self.update_objects();
+    }
+
+    fn update_objects(&mut self) {
+        let (sprite_width, sprite_height) = self.sprite_size.to_width_height();
+
+        for (i, (_, object)) in self.sprites.iter_mut().enumerate() {
+            let offset = (
+                (i % self.columns * sprite_width) as i32,
+                (i / self.columns * sprite_height) as i32,
+            );
+
+            object
+                .set_position(self.position + offset.into())
+                .set_priority(self.priority)
+                .set_z(self.z);
+
+            if self.visible {
+                object.show();
+            } else {
+                object.hide();
+            }
+        }
+    }
+
+    /// Sets the position of the top left of the text on screen.
+    pub fn set_position(&mut self, position: Vector2D<i32>) -> &mut Self {
+        self.position = position;
+        self.update_objects();
+        self
+    }
+
+    pub fn position(&self) -> Vector2D<i32> {
+        self.position
+    }
+
+    pub fn show(&mut self) -> &mut Self {
+        self.visible = true;
+        self.update_objects();
+        self
+    }
+
+    pub fn hide(&mut self) -> &mut Self {
+        self.visible = false;
+        self.update_objects();
+        self
+    }
+
+    pub fn set_priority(&mut self, priority: Priority) -> &mut Self {
+        self.priority = priority;
+        self.update_objects();
+        self
+    }
+
+    pub fn set_z(&mut self, z: i32) -> &mut Self {
+        self.z = z;
+        self.update_objects();
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FONT: Font = crate::include_font!("examples/font/yoster.ttf", 12);
+    static PALETTE: Palette16 = Palette16::new([0xffff; 16]);
+
+    #[test_case]
+    fn object_text_uses_enough_sprites(gba: &mut crate::Gba) {
+        let controller = gba.display.object.get();
+
+        {
+            let mut text = ObjectText::new(&FONT, &controller, &PALETTE, 1).with_outline(2);
+
+            text.set_text("1");
+            assert_eq!(text.sprites.len(), 1);
+
+            let (sprite, _) = &mut text.sprites[0];
+            let (width, height) = sprite.size().to_width_height();
+            let colours_used = (0..height)
+                .flat_map(|y| (0..width).map(move |x| (x, y)))
+                .fold(0u16, |used, (x, y)| used | (1 << sprite.pixel(x, y)));
+            assert_eq!(colours_used, 0b111);
+
+            text.set_text("Hello, World!\nSecond line");
+            assert_eq!(text.columns, 3);
+            assert_eq!(text.sprites.len(), 6);
+
+            text.set_position((10, 10).into()).show();
+            controller.commit();
+        }
+
+        controller.commit();
+    }
+}
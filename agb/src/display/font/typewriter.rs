@@ -0,0 +1,397 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+use core::ops::Range;
+
+use super::{Font, TextAlignment, TextLayout, TextRenderer};
+use crate::fixnum::Vector2D;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Command {
+    Colour(u8),
+    Pause(u32),
+    Speed(u32),
+}
+
+/// Parses the contents of a markup tag, returning `None` if it isn't valid.
+fn parse_command(tag: &str) -> Option<Command> {
+    let (name, value) = tag.split_once(':')?;
+
+    match name {
+        "c" => value
+            .parse()
+            .ok()
+            .filter(|&colour: &u8| colour < 16)
+            .map(Command::Colour),
+        "p" => value.parse().ok().map(Command::Pause),
+        "s" => value
+            .parse()
+            .ok()
+            .map(|speed: u32| Command::Speed(speed.max(1))),
+        _ => None,
+    }
+}
+
+/// Splits the markup into the text to display and the commands to run, along
+/// with the position in the text at which each command runs. Invalid tags are
+/// kept in the text as they are.
+fn parse_markup(markup: &str) -> (String, Vec<(usize, Command)>) {
+    let mut text = String::new();
+    let mut commands = Vec::new();
+
+    let mut chars = markup.chars();
+    while let Some(c) = chars.next() {
+        let rest = chars.as_str();
+
+        match c {
+            '{' if rest.starts_with('{') => {
+                chars.next();
+                text.push('{');
+            }
+            '}' if rest.starts_with('}') => {
+                chars.next();
+                text.push('}');
+            }
+            '{' => {
+                let tag = rest
+                    .split_once('}')
+                    .and_then(|(tag, after)| Some((parse_command(tag)?, after)));
+
+                match tag {
+                    Some((command, after)) => {
+                        commands.push((text.len(), command));
+                        chars = after.chars();
+                    }
+                    None => text.push('{'),
+                }
+            }
+            c => text.push(c),
+        }
+    }
+
+    (text, commands)
+}
+
+/// Reveals text a few letters at a time, as is common for dialogue. The text
+/// can contain markup to change how it is revealed:
+///
+/// * `{c:N}` changes the foreground colour to palette index `N`.
+/// * `{p:N}` pauses for `N` frames.
+/// * `{s:N}` changes the speed to `N` letters per frame.
+/// * `{{` and `}}` are a literal `{` and `}`.
+///
+/// Tags which aren't valid, such as a colour outside of the palette, are shown
+/// as they are.
+///
+/// Text is laid out in the same way as [`TextLayout`], with each page being
+/// revealed separately.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// let mut typewriter = Typewriter::new(
+///     &FONT,
+///     "Hello, {p:30}{c:3}world{c:1}!",
+///     (200, 40).into(),
+///     TextAlignment::Left,
+/// );
+///
+/// let mut writer = FONT.render_text((1u16, 15u16).into(), 1, 0, &mut bg, &mut vram);
+///
+/// while !typewriter.is_page_finished() {
+///     input.update();
+///     if input.is_just_pressed(Button::A) {
+///         typewriter.skip();
+///     }
+///
+///     typewriter.update(&mut writer);
+///
+///     vblank.wait_for_vblank();
+///     bg.commit(&mut vram);
+/// }
+/// ```
+pub struct Typewriter {
+    text: String,
+    commands: Vec<(usize, Command)>,
+    pages: Vec<Vec<(Range<usize>, Vector2D<i32>)>>,
+
+    page: usize,
+    line: usize,
+    line_started: bool,
+    position: usize,
+    next_command: usize,
+
+    speed: u32,
+    pause: u32,
+    skipping: bool,
+    /// Set by the last colour tag, so that it can be reapplied to a new renderer
+    /// when the page changes.
+    colour: Option<u8>,
+}
+
+impl Typewriter {
+    /// Lays out the markup in a box of the given size in pixels.
+    pub fn new(font: &Font, markup: &str, size: Vector2D<i32>, alignment: TextAlignment) -> Self {
+        let (text, commands) = parse_markup(markup);
+
+        let layout = TextLayout::new(font, &text, size, alignment);
+        let pages = (0..layout.page_count())
+            .map(|page| layout.line_positions(page).collect())
+            .collect();
+
+        Self {
+            text,
+            commands,
+            pages,
+
+            page: 0,
+            line: 0,
+            line_started: false,
+            position: 0,
+            next_command: 0,
+
+            speed: 1,
+            pause: 0,
+            skipping: false,
+            colour: None,
+        }
+    }
+
+    /// Reveals the next letters of the current page. This should be called once
+    /// per frame with a renderer for the same font as the typewriter, which is
+    /// updated so that the new letters are shown once the background is
+    /// committed.
+    pub fn update(&mut self, renderer: &mut TextRenderer) {
+        self.reveal(renderer);
+        renderer.update();
+    }
+
+    fn reveal(&mut self, renderer: &mut TextRenderer) {
+        if self.pause > 0 && !self.skipping {
+            self.pause -= 1;
+            return;
+        }
+
+        let mut revealed = 0;
+        while self.skipping || revealed < self.speed {
+            let (range, position) = match self.pages[self.page].get(self.line) {
+                Some(line) => line.clone(),
+                None => return,
+            };
+
+            if !self.line_started {
+                self.position = self.position.max(range.start);
+                renderer.set_cursor(position);
+                if let Some(colour) = self.colour {
+                    renderer.set_foreground_colour(colour);
+                }
+                self.line_started = true;
+            }
+
+            while let Some(&(offset, command)) = self.commands.get(self.next_command) {
+                if offset > self.position {
+                    break;
+                }
+
+                self.next_command += 1;
+
+                match command {
+                    Command::Colour(colour) => {
+                        self.colour = Some(colour);
+                        renderer.set_foreground_colour(colour);
+                    }
+                    Command::Speed(speed) => self.speed = speed,
+                    Command::Pause(frames) => {
+                        if !self.skipping && frames > 0 {
+                            // this frame is the first of the pause if it hasn't
+                            // revealed anything yet
+                            self.pause = if revealed == 0 { frames - 1 } else { frames };
+                            return;
+                        }
+                    }
+                }
+            }
+
+            if self.position >= range.end {
+                self.line += 1;
+                self.line_started = false;
+                continue;
+            }
+
+            let letter_len = self.text[self.position..]
+                .chars()
+                .next()
+                .unwrap()
+                .len_utf8();
+            let letter = &self.text[self.position..self.position + letter_len];
+            renderer
+                .write_str(letter)
+                .expect("Writing to a text renderer can't fail");
+
+            self.position += letter_len;
+            revealed += 1;
+
+            if self.position >= range.end {
+                self.line += 1;
+                self.line_started = false;
+            }
+        }
+    }
+
+    /// Reveals the rest of the current page in the next update, ignoring any
+    /// pauses. This is usually done when the player presses A.
+    pub fn skip(&mut self) {
+        self.skipping = true;
+    }
+
+    /// Whether every letter on the current page has been revealed.
+    pub fn is_page_finished(&self) -> bool {
+        self.line >= self.pages[self.page].len()
+    }
+
+    /// Whether every letter on every page has been revealed.
+    pub fn is_finished(&self) -> bool {
+        self.is_page_finished() && self.page + 1 >= self.pages.len()
+    }
+
+    /// Moves on to the next page, returning `false` if this is the last page.
+    /// The text already rendered should be cleared before updating again, for
+    /// example by using a new [`TextRenderer`]. The colour set by the markup
+    /// carries on to the new renderer.
+    pub fn next_page(&mut self) -> bool {
+        if self.page + 1 >= self.pages.len() {
+            return false;
+        }
+
+        self.page += 1;
+        self.line = 0;
+        self.line_started = false;
+        self.pause = 0;
+        self.skipping = false;
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::tiled::RegularBackgroundSize;
+    use crate::display::Priority;
+
+    const FONT: Font = crate::include_font!("examples/font/yoster.ttf", 12);
+
+    #[test_case]
+    fn typewriter_markup_is_parsed(_gba: &mut crate::Gba) {
+        let (text, commands) = parse_markup("Hi {c:3}there{p:30}{s:2}! {{ok}}");
+
+        assert_eq!(text, "Hi there! {ok}");
+        assert_eq!(
+            commands,
+            [
+                (3, Command::Colour(3)),
+                (8, Command::Pause(30)),
+                (8, Command::Speed(2)),
+            ]
+        );
+    }
+
+    #[test_case]
+    fn typewriter_invalid_markup_is_kept_as_text(_gba: &mut crate::Gba) {
+        let markup = "{c:16}{c:300}{p:x}{q:1}{nope} {s:1}{c:2";
+        let (text, commands) = parse_markup(markup);
+
+        assert_eq!(text, "{c:16}{c:300}{p:x}{q:1}{nope} {c:2");
+        assert_eq!(commands, [(30, Command::Speed(1))]);
+    }
+
+    #[test_case]
+    fn typewriter_reveals_text_over_time(gba: &mut crate::Gba) {
+        let (gfx, mut vram) = gba.display.video.tiled0();
+        let mut bg = gfx.background(Priority::P0, RegularBackgroundSize::Background32x32);
+
+        let mut typewriter = Typewriter::new(
+            &FONT,
+            "ab{p:3}cd{s:2}efgh",
+            (200, 40).into(),
+            TextAlignment::Left,
+        );
+
+        {
+            let mut writer = FONT.render_text((0u16, 0u16).into(), 1, 0, &mut bg, &mut vram);
+
+            let mut frames = 0;
+            while !typewriter.is_page_finished() {
+                typewriter.update(&mut writer);
+                frames += 1;
+            }
+
+            // 2 letters, 3 paused, 2 letters then 4 letters at 2 per frame
+            assert_eq!(frames, 2 + 3 + 2 + 2);
+            assert!(typewriter.is_finished());
+            assert!(!typewriter.next_page());
+        }
+
+        {
+            let mut typewriter = Typewriter::new(
+                &FONT,
+                "{s:3}ab{p:2}cd",
+                (200, 40).into(),
+                TextAlignment::Left,
+            );
+            let mut writer = FONT.render_text((0u16, 0u16).into(), 1, 0, &mut bg, &mut vram);
+
+            let mut frames = 0;
+            while !typewriter.is_page_finished() {
+                typewriter.update(&mut writer);
+                frames += 1;
+            }
+
+            // the pause starts after the frame which revealed the first letters
+            assert_eq!(frames, 1 + 2 + 1);
+        }
+
+        let mut typewriter = Typewriter::new(
+            &FONT,
+            "{p:100}abcdef",
+            (200, 40).into(),
+            TextAlignment::Left,
+        );
+        let mut writer = FONT.render_text((0u16, 0u16).into(), 1, 0, &mut bg, &mut vram);
+
+        typewriter.skip();
+        typewriter.update(&mut writer);
+        assert!(typewriter.is_page_finished());
+    }
+
+    #[test_case]
+    fn typewriter_colour_carries_on_to_the_next_page(gba: &mut crate::Gba) {
+        let (gfx, mut vram) = gba.display.video.tiled0();
+        let mut bg = gfx.background(Priority::P0, RegularBackgroundSize::Background32x32);
+
+        let mut typewriter = Typewriter::new(
+            &FONT,
+            "a{c:3}b\nc",
+            (200, FONT.line_height()).into(),
+            TextAlignment::Left,
+        );
+
+        {
+            let mut writer = FONT.render_text((0u16, 0u16).into(), 1, 0, &mut bg, &mut vram);
+            while !typewriter.is_page_finished() {
+                typewriter.update(&mut writer);
+            }
+            assert_eq!(writer.style.foreground_colour, 3);
+        }
+
+        assert!(typewriter.next_page());
+
+        let mut writer = FONT.render_text((0u16, 0u16).into(), 1, 0, &mut bg, &mut vram);
+        assert_eq!(writer.style.foreground_colour, 1);
+        while !typewriter.is_page_finished() {
+            typewriter.update(&mut writer);
+        }
+        assert_eq!(writer.style.foreground_colour, 3);
+        assert!(typewriter.is_finished());
+    }
+}
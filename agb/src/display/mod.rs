@@ -4,31 +4,41 @@ use bitflags::bitflags;
 use modular_bitfield::BitfieldSpecifier;
 use video::Video;
 
+use self::blend::Blend;
 use self::object::ObjectController;
+use self::window::Window;
 
 /// Graphics mode 3. Bitmap mode that provides a 16-bit colour framebuffer.
 pub mod bitmap3;
 /// Graphics mode 4. Bitmap 4 provides two 8-bit paletted framebuffers with page switching.
 pub mod bitmap4;
+/// Colour special effects such as alpha blending and fading.
+pub mod blend;
+/// On screen text console for debugging without mgba.
+pub mod console;
 /// Test logo of agb.
 pub mod example_logo;
 /// Implements sprites.
 pub mod object;
 /// Palette type.
 pub mod palette16;
+/// On screen frame rate and CPU load for spotting slowdowns.
+pub mod performance;
 /// Data produced by agb-image-converter
 pub mod tile_data;
 /// Graphics mode 0. Four regular backgrounds.
 pub mod tiled;
 /// Giving out graphics mode.
 pub mod video;
+/// Masking regions of the screen using windows.
+pub mod window;
 
 mod font;
-pub use font::{Font, FontLetter};
+pub use font::{Font, FontLetter, ObjectText, TextAlignment, TextLayout, TextRenderer, Typewriter};
 
 const DISPLAY_CONTROL: MemoryMapped<u16> = unsafe { MemoryMapped::new(0x0400_0000) };
 pub(crate) const DISPLAY_STATUS: MemoryMapped<u16> = unsafe { MemoryMapped::new(0x0400_0004) };
-const VCOUNT: MemoryMapped<u16> = unsafe { MemoryMapped::new(0x0400_0006) };
+pub(crate) const VCOUNT: MemoryMapped<u16> = unsafe { MemoryMapped::new(0x0400_0006) };
 
 bitflags! {
     struct GraphicsSettings: u16 {
@@ -67,6 +77,8 @@ enum DisplayMode {
 pub struct Display {
     pub video: Video,
     pub object: ObjectDistribution,
+    pub blend: Blend,
+    pub window: Window,
 }
 
 #[non_exhaustive]
@@ -83,6 +95,8 @@ impl Display {
         Display {
             video: Video {},
             object: ObjectDistribution {},
+            blend: Blend::new(),
+            window: Window::new(),
         }
     }
 }
@@ -116,7 +130,7 @@ pub fn busy_wait_for_vblank() {
     while VCOUNT.get() < 160 {}
 }
 
-#[derive(BitfieldSpecifier, Clone, Copy)]
+#[derive(BitfieldSpecifier, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Priority {
     P0 = 0,
     P1 = 1,
@@ -0,0 +1,214 @@
+use super::{Direction, Sprite, Tag};
+
+/// The length of a single frame (280896 cycles at 2^24Hz) in microseconds.
+const FRAME_LENGTH_MICROSECONDS: u32 = 16_743;
+
+/// Events which are emitted by [`Animation::update`] and [`Animation::advance`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnimationEvent {
+    /// The animation reached its end and started again from the beginning.
+    Looped,
+    /// The animation reached its end and isn't looping, so will now stay on the
+    /// final frame. For a ping-pong animation this is the first frame.
+    Finished,
+}
+
+/// Plays the frames of a [`Tag`] using the frame durations from the aseprite
+/// file and honouring the tag's forward, reverse or ping-pong direction.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// let mut animation = WALKING.animation();
+///
+/// loop {
+///     if animation.update() == Some(AnimationEvent::Finished) {
+///         // ...
+///     }
+///
+///     object.set_sprite(controller.sprite(animation.sprite()));
+///
+///     vblank.wait_for_vblank();
+///     controller.commit();
+/// }
+/// ```
+pub struct Animation {
+    tag: &'static Tag,
+    step: usize,
+    elapsed: u32,
+    looping: bool,
+    finished: bool,
+}
+
+impl Animation {
+    /// Creates a new looping animation which starts at the first frame of the tag.
+    pub fn new(tag: &'static Tag) -> Self {
+        Self {
+            tag,
+            step: 0,
+            elapsed: 0,
+            looping: true,
+            finished: false,
+        }
+    }
+
+    /// Sets whether the animation should start again once it reaches the end or
+    /// stop on the final frame.
+    pub fn set_looping(&mut self, looping: bool) -> &mut Self {
+        self.looping = looping;
+        self
+    }
+
+    /// Moves the animation back to its first frame.
+    pub fn restart(&mut self) -> &mut Self {
+        self.step = 0;
+        self.elapsed = 0;
+        self.finished = false;
+        self
+    }
+
+    /// Changes the tag being played and restarts the animation. Does nothing if
+    /// the tag is already being played.
+    pub fn set_tag(&mut self, tag: &'static Tag) -> &mut Self {
+        if !core::ptr::eq(self.tag, tag) {
+            self.tag = tag;
+            self.restart();
+        }
+
+        self
+    }
+
+    pub fn tag(&self) -> &'static Tag {
+        self.tag
+    }
+
+    /// The index of the current frame within the tag.
+    pub fn frame(&self) -> usize {
+        let len = self.tag.len;
+        match self.tag.direction {
+            Direction::Forward => self.step,
+            Direction::Backward => len - 1 - self.step,
+            Direction::Pingpong => {
+                if self.step < len {
+                    self.step
+                } else {
+                    2 * (len - 1) - self.step
+                }
+            }
+        }
+    }
+
+    /// The sprite of the current frame of the animation.
+    pub fn sprite(&self) -> &'static Sprite {
+        self.tag.sprite(self.frame())
+    }
+
+    /// Whether a non looping animation has reached its final frame.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Advances the animation by the length of a single frame. This should be
+    /// called once per frame, for example after waiting for vblank.
+    pub fn update(&mut self) -> Option<AnimationEvent> {
+        self.advance(FRAME_LENGTH_MICROSECONDS)
+    }
+
+    /// Advances the animation by the given number of microseconds. If the
+    /// animation loops or finishes as a result then the corresponding event is
+    /// returned.
+    pub fn advance(&mut self, microseconds: u32) -> Option<AnimationEvent> {
+        if self.finished {
+            return None;
+        }
+
+        let mut event = None;
+        self.elapsed += microseconds;
+
+        loop {
+            let duration = (self.tag.frame_duration(self.frame()) as u32 * 1000).max(1);
+            if self.elapsed < duration {
+                break;
+            }
+
+            if self.step + 1 < self.number_of_steps() {
+                self.elapsed -= duration;
+                self.step += 1;
+            } else if self.looping {
+                self.elapsed -= duration;
+                self.step = 0;
+                event = Some(AnimationEvent::Looped);
+            } else {
+                self.elapsed = 0;
+                self.finished = true;
+                return Some(AnimationEvent::Finished);
+            }
+        }
+
+        event
+    }
+
+    fn number_of_steps(&self) -> usize {
+        let len = self.tag.len;
+        match self.tag.direction {
+            Direction::Forward | Direction::Backward => len,
+            // a ping-pong animation which isn't looping goes back to the first
+            // frame at the end, rather than that being the start of the next loop
+            Direction::Pingpong if len > 1 => 2 * (len - 1) + usize::from(!self.looping),
+            Direction::Pingpong => 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::object::Size;
+    use crate::display::palette16::Palette16;
+
+    const PALETTE: Palette16 = Palette16::new([0; 16]);
+    const SPRITES: &[Sprite] = &[
+        Sprite::new(&PALETTE, &[], Size::S8x8),
+        Sprite::new(&PALETTE, &[], Size::S8x8),
+        Sprite::new(&PALETTE, &[], Size::S8x8),
+    ];
+    const DURATIONS: &[u16] = &[100, 50, 100];
+
+    const FORWARD: &Tag = &Tag::new(SPRITES, DURATIONS, 0, 2, 0);
+    const PINGPONG: &Tag = &Tag::new(SPRITES, DURATIONS, 0, 2, 2);
+
+    #[test_case]
+    fn animation_respects_frame_durations(_gba: &mut crate::Gba) {
+        let mut animation = FORWARD.animation();
+
+        assert_eq!(animation.advance(99_000), None);
+        assert_eq!(animation.frame(), 0);
+        assert_eq!(animation.advance(1_000), None);
+        assert_eq!(animation.frame(), 1);
+        assert_eq!(animation.advance(50_000), None);
+        assert_eq!(animation.frame(), 2);
+        assert_eq!(animation.advance(100_000), Some(AnimationEvent::Looped));
+        assert_eq!(animation.frame(), 0);
+    }
+
+    #[test_case]
+    fn animation_ping_pong_finishes(_gba: &mut crate::Gba) {
+        let mut animation = PINGPONG.animation();
+        animation.set_looping(false);
+
+        let frames: alloc::vec::Vec<_> = core::iter::from_fn(|| {
+            let frame = animation.frame();
+            match animation.advance(50_000) {
+                Some(AnimationEvent::Finished) => None,
+                _ => Some(frame),
+            }
+        })
+        .collect();
+
+        assert_eq!(frames, [0, 0, 1, 2, 2, 1, 0]);
+        assert!(animation.is_finished());
+        assert_eq!(animation.frame(), 0);
+        assert_eq!(animation.advance(1_000_000), None);
+        assert_eq!(animation.frame(), 0);
+    }
+}
@@ -0,0 +1,219 @@
+use crate::fixnum::{Rect, Vector2D};
+
+/// A 1 bit per pixel mask of which pixels of a sprite are solid, for pixel
+/// perfect collision detection. These are generated by
+/// `include_aseprite!(collision_masks; ...)` and can be found using
+/// [`Sprite::collision_mask`][super::Sprite::collision_mask].
+///
+/// Each row is stored as a `u64` where bit `x` is set if the pixel in column `x`
+/// is solid. The size of the mask is the size of the hardware sprite, so that
+/// flipping the mask matches flipping the sprite.
+pub struct CollisionMask {
+    width: usize,
+    height: usize,
+    rows: &'static [u64],
+}
+
+impl CollisionMask {
+    #[doc(hidden)]
+    pub const fn new(width: usize, height: usize, rows: &'static [u64]) -> Self {
+        assert!(width <= 64);
+        assert!(rows.len() == height);
+        Self {
+            width,
+            height,
+            rows,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Whether the pixel at the given location in the unflipped sprite is solid.
+    pub fn is_solid(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height && self.rows[y] & (1 << x) != 0
+    }
+
+    /// Places the mask with its top left corner at the given position, ready to
+    /// test for overlap with another placed mask.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let player_mask = player_sprite.collision_mask().unwrap();
+    /// let bullet_mask = bullet_sprite.collision_mask().unwrap();
+    ///
+    /// if player_mask
+    ///     .at(player_position)
+    ///     .with_hflip(facing_left)
+    ///     .overlaps(&bullet_mask.at(bullet_position))
+    /// {
+    ///     // ...
+    /// }
+    /// ```
+    pub fn at(&self, position: Vector2D<i32>) -> PlacedCollisionMask<'_> {
+        PlacedCollisionMask {
+            mask: self,
+            position,
+            hflip: false,
+            vflip: false,
+        }
+    }
+}
+
+/// A [`CollisionMask`] at a position on screen, optionally flipped in the same
+/// way as the object displaying it.
+#[derive(Clone, Copy)]
+pub struct PlacedCollisionMask<'a> {
+    mask: &'a CollisionMask,
+    position: Vector2D<i32>,
+    hflip: bool,
+    vflip: bool,
+}
+
+impl PlacedCollisionMask<'_> {
+    #[must_use]
+    pub fn with_hflip(self, hflip: bool) -> Self {
+        Self { hflip, ..self }
+    }
+
+    #[must_use]
+    pub fn with_vflip(self, vflip: bool) -> Self {
+        Self { vflip, ..self }
+    }
+
+    /// The area covered by the mask, equivalent to the area covered by the sprite.
+    pub fn bounding_rect(&self) -> Rect<i32> {
+        Rect::new(
+            self.position,
+            (self.mask.width as i32, self.mask.height as i32).into(),
+        )
+    }
+
+    /// The row of the mask at the given y offset from the top of the placed
+    /// mask, with the flips applied.
+    fn row(&self, y: i32) -> u64 {
+        let mask = self.mask;
+
+        let y = if self.vflip {
+            mask.height - 1 - y as usize
+        } else {
+            y as usize
+        };
+
+        let row = mask.rows[y];
+        if self.hflip {
+            row.reverse_bits() >> (64 - mask.width)
+        } else {
+            row
+        }
+    }
+
+    /// Whether any solid pixel of this mask overlaps with a solid pixel of the
+    /// other mask.
+    pub fn overlaps(&self, other: &PlacedCollisionMask) -> bool {
+        if !self.bounding_rect().touches(other.bounding_rect()) {
+            return false;
+        }
+
+        let top = self.position.y.max(other.position.y);
+        let bottom = (self.position.y + self.mask.height as i32)
+            .min(other.position.y + other.mask.height as i32);
+
+        // the horizontal offset of the other mask relative to this one
+        let dx = other.position.x - self.position.x;
+        if dx >= self.mask.width as i32 || -dx >= other.mask.width as i32 {
+            return false;
+        }
+
+        (top..bottom).any(|y| {
+            let row = self.row(y - self.position.y);
+            let other_row = other.row(y - other.position.y);
+
+            if dx >= 0 {
+                row & (other_row << dx) != 0
+            } else {
+                (row << -dx) & other_row != 0
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // An L shape in the left column and bottom row of an 8x8 sprite
+    const L_SHAPE: CollisionMask = CollisionMask::new(
+        8,
+        8,
+        &[
+            0b0000_0001,
+            0b0000_0001,
+            0b0000_0001,
+            0b0000_0001,
+            0b0000_0001,
+            0b0000_0001,
+            0b0000_0001,
+            0b1111_1111,
+        ],
+    );
+
+    const DOT: CollisionMask = CollisionMask::new(8, 8, &[1, 0, 0, 0, 0, 0, 0, 0]);
+
+    #[test_case]
+    fn collision_masks_respect_solid_pixels(_gba: &mut crate::Gba) {
+        let l_shape = L_SHAPE.at((10, 10).into());
+
+        assert!(l_shape.overlaps(&DOT.at((10, 10).into())));
+        assert!(l_shape.overlaps(&DOT.at((17, 17).into())));
+        // inside the bounding box but not touching the L
+        assert!(!l_shape.overlaps(&DOT.at((14, 12).into())));
+        // outside the bounding box entirely
+        assert!(!l_shape.overlaps(&DOT.at((18, 10).into())));
+        assert!(!l_shape.overlaps(&DOT.at((9, 10).into())));
+    }
+
+    #[test_case]
+    fn collision_masks_respect_flips(_gba: &mut crate::Gba) {
+        let dot = DOT.at((17, 10).into());
+
+        assert!(!L_SHAPE.at((10, 10).into()).overlaps(&dot));
+        assert!(L_SHAPE.at((10, 10).into()).with_hflip(true).overlaps(&dot));
+        assert!(!L_SHAPE
+            .at((10, 10).into())
+            .with_vflip(true)
+            .overlaps(&DOT.at((15, 17).into())));
+        assert!(L_SHAPE
+            .at((10, 10).into())
+            .with_vflip(true)
+            .overlaps(&DOT.at((15, 10).into())));
+    }
+
+    #[test_case]
+    fn collision_masks_are_generated_for_aseprite_files(_gba: &mut crate::Gba) {
+        use crate::display::object::{Graphics, Tag};
+
+        const GRAPHICS: &Graphics = crate::include_aseprite!(
+            collision_masks;
+            "../examples/the-purple-night/gfx/objects.aseprite"
+        );
+        const EMU: &Tag = GRAPHICS.tags().get("emu - idle");
+
+        let sprite = EMU.sprite(0);
+        let mask = sprite.collision_mask().unwrap();
+
+        assert_eq!(
+            (mask.width(), mask.height()),
+            sprite.size().to_width_height()
+        );
+
+        let position = (20, 20).into();
+        assert!(mask.at(position).overlaps(&mask.at(position)));
+    }
+}
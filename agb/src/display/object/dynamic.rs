@@ -0,0 +1,172 @@
+use core::marker::PhantomData;
+use core::slice;
+
+use super::{
+    get_object_controller, ObjectController, Size, SpriteBorrow, SpriteControllerInner, SpriteId,
+    Storage, SPRITE_ALLOCATOR,
+};
+use crate::display::palette16::Palette16;
+
+/// A sprite whose tile data is written at runtime rather than included from
+/// rom, for example to display text. The tile data is stored directly in
+/// sprite VRAM, so changes show straight away on any object using the sprite.
+///
+/// Objects using the sprite keep it in VRAM even after this is dropped.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// let mut sprite = object_controller.dynamic_sprite(Size::S8x8, &PALETTE);
+/// sprite.clear(0);
+/// sprite.set_pixel(3, 4, 1);
+///
+/// let mut object = object_controller.object(sprite.sprite());
+/// object.show();
+/// ```
+pub struct DynamicSprite<'a> {
+    borrow: SpriteBorrow<'a>,
+}
+
+impl<'a> DynamicSprite<'a> {
+    pub fn size(&self) -> Size {
+        self.borrow.size
+    }
+
+    /// A borrow of the sprite which can be given to an object.
+    pub fn sprite(&self) -> SpriteBorrow<'a> {
+        Clone::clone(&self.borrow)
+    }
+
+    /// The tile data of the sprite. Each `u32` is a row of 8 pixels within a
+    /// tile with 4 bits per pixel, and the tiles go left to right then top to
+    /// bottom.
+    pub fn tile_data(&mut self) -> &mut [u32] {
+        let size = self.borrow.size;
+        let storage = Storage {
+            location: self.borrow.sprite_location,
+            count: 0,
+        };
+
+        unsafe {
+            slice::from_raw_parts_mut(
+                storage.as_sprite_ptr().cast(),
+                size.number_of_tiles() * super::BYTES_PER_TILE_4BPP / 4,
+            )
+        }
+    }
+
+    /// Sets every pixel to the given palette index.
+    pub fn clear(&mut self, colour: u8) {
+        let colour = colour as u32 & 0xf;
+        self.tile_data().fill(colour * 0x1111_1111);
+    }
+
+    /// Sets the pixel to the given palette index, where 0 is transparent.
+    pub fn set_pixel(&mut self, x: usize, y: usize, colour: u8) {
+        let (width, height) = self.size().to_width_height();
+        assert!(x < width && y < height, "Pixel is outside of the sprite");
+
+        let tile = (y / 8) * (width / 8) + x / 8;
+        let shift = (x % 8) * 4;
+
+        let line = &mut self.tile_data()[tile * 8 + y % 8];
+        *line = (*line & !(0xf << shift)) | ((colour as u32 & 0xf) << shift);
+    }
+
+    /// The palette index of the pixel.
+    pub fn pixel(&mut self, x: usize, y: usize) -> u8 {
+        let (width, _) = self.size().to_width_height();
+
+        let tile = (y / 8) * (width / 8) + x / 8;
+        let shift = (x % 8) * 4;
+
+        ((self.tile_data()[tile * 8 + y % 8] >> shift) & 0xf) as u8
+    }
+}
+
+impl ObjectController {
+    /// Allocates space in sprite VRAM for a sprite which can be drawn to at
+    /// runtime. The contents of the sprite are initially undefined.
+    pub fn dynamic_sprite(&self, size: Size, palette: &'static Palette16) -> DynamicSprite<'_> {
+        self.try_get_dynamic_sprite(size, palette)
+            .expect("No slot for sprite available")
+    }
+
+    pub fn try_get_dynamic_sprite(
+        &self,
+        size: Size,
+        palette: &'static Palette16,
+    ) -> Option<DynamicSprite<'_>> {
+        let s = unsafe { get_object_controller(&self.phantom) };
+        let borrow = unsafe {
+            s.very_unsafe_borrow()
+                .sprite_controller
+                .try_get_dynamic_sprite(size, palette)?
+        };
+
+        Some(DynamicSprite { borrow })
+    }
+}
+
+impl SpriteControllerInner {
+    fn try_get_dynamic_sprite(
+        &mut self,
+        size: Size,
+        palette: &'static Palette16,
+    ) -> Option<SpriteBorrow<'static>> {
+        let dest = unsafe { SPRITE_ALLOCATOR.alloc(size.layout())? };
+
+        let palette_location = match self.palette(palette) {
+            Some(location) => location,
+            None => {
+                unsafe { SPRITE_ALLOCATOR.dealloc(dest.as_ptr(), size.layout()) };
+                return None;
+            }
+        };
+
+        let storage = Storage::from_sprite_ptr(dest);
+        let id = SpriteId(dest.as_ptr() as usize);
+        self.sprite.insert(id, storage);
+
+        Some(SpriteBorrow {
+            id,
+            size,
+            palette,
+            sprite_location: storage.location,
+            palette_location,
+            phantom: PhantomData,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::palette16::Palette16;
+
+    static PALETTE: Palette16 = Palette16::new([0xffff; 16]);
+
+    #[test_case]
+    fn dynamic_sprites_can_be_drawn_to(gba: &mut crate::Gba) {
+        let controller = gba.display.object.get();
+
+        {
+            let mut sprite = controller.dynamic_sprite(Size::S16x8, &PALETTE);
+            sprite.clear(0);
+            sprite.set_pixel(9, 2, 5);
+
+            assert_eq!(sprite.pixel(9, 2), 5);
+            assert_eq!(sprite.pixel(8, 2), 0);
+            // the second tile, third row and second pixel
+            assert_eq!(sprite.tile_data()[8 + 2], 5 << 4);
+
+            let mut object = controller.object(sprite.sprite());
+            object.set_position((10, 10).into()).show();
+            drop(sprite);
+
+            controller.commit();
+        }
+
+        controller.commit();
+    }
+}
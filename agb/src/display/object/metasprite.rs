@@ -0,0 +1,348 @@
+use alloc::vec::Vec;
+
+use super::{Object, ObjectController, Sprite};
+use crate::display::Priority;
+use crate::fixnum::Vector2D;
+
+/// A single hardware sprite within a [`MetaSprite`], along with where it sits
+/// relative to the top left corner of the metasprite.
+pub struct MetaSpritePart {
+    sprite: &'static Sprite,
+    offset: Vector2D<i32>,
+}
+
+impl MetaSpritePart {
+    pub const fn new(sprite: &'static Sprite, x: i32, y: i32) -> Self {
+        Self {
+            sprite,
+            offset: Vector2D { x, y },
+        }
+    }
+
+    pub fn sprite(&self) -> &'static Sprite {
+        self.sprite
+    }
+
+    pub fn offset(&self) -> Vector2D<i32> {
+        self.offset
+    }
+}
+
+/// A sprite made up of multiple hardware sprites. This allows for sprites
+/// larger than 64x64 or with irregular shapes.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// const BOSS: MetaSprite = MetaSprite::new(&[
+///     MetaSpritePart::new(BOSS_TAG.sprite(0), 0, 0),
+///     MetaSpritePart::new(BOSS_TAG.sprite(1), 64, 0),
+///     MetaSpritePart::new(BOSS_TAG.sprite(2), 16, 64),
+/// ]);
+///
+/// let mut boss = object_controller.meta_object(&BOSS);
+/// boss.set_position((100, 20).into()).set_hflip(true);
+/// ```
+pub struct MetaSprite {
+    parts: &'static [MetaSpritePart],
+    size: Vector2D<i32>,
+}
+
+impl MetaSprite {
+    pub const fn new(parts: &'static [MetaSpritePart]) -> Self {
+        let mut width = 0;
+        let mut height = 0;
+
+        let mut i = 0;
+        while i < parts.len() {
+            let part = &parts[i];
+            let (part_width, part_height) = part.sprite.size().to_width_height();
+
+            assert!(
+                part.offset.x >= 0 && part.offset.y >= 0,
+                "Metasprite part offsets must not be negative"
+            );
+
+            if part.offset.x + part_width as i32 > width {
+                width = part.offset.x + part_width as i32;
+            }
+            if part.offset.y + part_height as i32 > height {
+                height = part.offset.y + part_height as i32;
+            }
+
+            i += 1;
+        }
+
+        Self {
+            parts,
+            size: Vector2D {
+                x: width,
+                y: height,
+            },
+        }
+    }
+
+    pub fn parts(&self) -> &'static [MetaSpritePart] {
+        self.parts
+    }
+
+    /// The size of the bounding box of all the parts of the metasprite.
+    pub fn size(&self) -> Vector2D<i32> {
+        self.size
+    }
+
+    /// Where the given part should be placed relative to the top left of the
+    /// metasprite. When flipped, the offsets are mirrored within the bounding
+    /// box of the metasprite.
+    fn part_offset(&self, part: &MetaSpritePart, hflip: bool, vflip: bool) -> Vector2D<i32> {
+        let (width, height) = part.sprite.size().to_width_height();
+        let mut offset = part.offset;
+
+        if hflip {
+            offset.x = self.size.x - offset.x - width as i32;
+        }
+        if vflip {
+            offset.y = self.size.y - offset.y - height as i32;
+        }
+
+        offset
+    }
+}
+
+/// Multiple [`Object`]s controlled as a single unit, displaying a [`MetaSprite`].
+pub struct MetaObject<'a> {
+    controller: &'a ObjectController,
+    metasprite: &'static MetaSprite,
+    objects: Vec<Object<'a>>,
+    position: Vector2D<i32>,
+    hflip: bool,
+    vflip: bool,
+    visible: bool,
+    priority: Priority,
+    z: i32,
+}
+
+impl ObjectController {
+    pub fn meta_object(&self, metasprite: &'static MetaSprite) -> MetaObject<'_> {
+        self.try_get_meta_object(metasprite)
+            .expect("Not enough objects available for metasprite")
+    }
+
+    /// Gets an object for every part of the metasprite, returning `None` if
+    /// there aren't enough objects or space for the sprites available.
+    pub fn try_get_meta_object(&self, metasprite: &'static MetaSprite) -> Option<MetaObject<'_>> {
+        let mut meta_object = MetaObject {
+            controller: self,
+            metasprite,
+            objects: Vec::with_capacity(metasprite.parts.len()),
+            position: (0, 0).into(),
+            hflip: false,
+            vflip: false,
+            visible: true,
+            priority: Priority::P0,
+            z: 0,
+        };
+
+        meta_object.try_set_metasprite(metasprite)?;
+
+        Some(meta_object)
+    }
+}
+
+impl<'a> MetaObject<'a> {
+    /// Changes the metasprite being displayed, for example to show the next
+    /// frame of an animation. Objects are reused where possible.
+    pub fn set_metasprite(&mut self, metasprite: &'static MetaSprite) -> &mut Self {
+        self.try_set_metasprite(metasprite)
+            .expect("Not enough objects available for metasprite");
+        self
+    }
+
+    fn try_set_metasprite(&mut self, metasprite: &'static MetaSprite) -> Option<()> {
+        // everything is allocated before anything is changed, so that running
+        // out of objects or sprite memory leaves the current metasprite intact
+        let mut sprites = Vec::with_capacity(metasprite.parts.len());
+        for part in metasprite.parts {
+            sprites.push(self.controller.try_get_sprite(part.sprite)?);
+        }
+
+        let mut new_objects = Vec::new();
+        for sprite in sprites.drain(self.objects.len().min(sprites.len())..) {
+            let mut object = self.controller.try_get_object(sprite)?;
+            object.set_priority(self.priority).set_z(self.z);
+            new_objects.push(object);
+        }
+
+        self.objects.truncate(sprites.len());
+        for (object, sprite) in self.objects.iter_mut().zip(sprites) {
+            object.set_sprite(sprite);
+        }
+        self.objects.append(&mut new_objects);
+
+        self.metasprite = metasprite;
+        self.update_objects();
+
+        Some(())
+    }
+
+    pub fn set_position(&mut self, position: Vector2D<i32>) -> &mut Self {
+        self.position = position;
+        self.update_objects();
+        self
+    }
+
+    pub fn position(&self) -> Vector2D<i32> {
+        self.position
+    }
+
+    /// Flips the metasprite horizontally, mirroring the offsets of each part as
+    /// well as flipping each individual sprite.
+    pub fn set_hflip(&mut self, flip: bool) -> &mut Self {
+        self.hflip = flip;
+        self.update_objects();
+        self
+    }
+
+    /// Flips the metasprite vertically, mirroring the offsets of each part as
+    /// well as flipping each individual sprite.
+    pub fn set_vflip(&mut self, flip: bool) -> &mut Self {
+        self.vflip = flip;
+        self.update_objects();
+        self
+    }
+
+    pub fn show(&mut self) -> &mut Self {
+        self.visible = true;
+        self.update_objects();
+        self
+    }
+
+    pub fn hide(&mut self) -> &mut Self {
+        self.visible = false;
+        self.update_objects();
+        self
+    }
+
+    pub fn set_priority(&mut self, priority: Priority) -> &mut Self {
+        self.priority = priority;
+        for object in self.objects.iter_mut() {
+            object.set_priority(priority);
+        }
+        self
+    }
+
+    pub fn set_z(&mut self, z: i32) -> &mut Self {
+        self.z = z;
+        for object in self.objects.iter_mut() {
+            object.set_z(z);
+        }
+        self
+    }
+
+    fn update_objects(&mut self) {
+        let metasprite = self.metasprite;
+
+        for (object, part) in self.objects.iter_mut().zip(metasprite.parts) {
+            if !self.visible {
+                object.hide();
+                continue;
+            }
+
+            let offset = metasprite.part_offset(part, self.hflip, self.vflip);
+
+            object
+                .set_hflip(self.hflip)
+                .set_vflip(self.vflip)
+                .set_position(self.position + offset)
+                .show();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::object::{Graphics, Tag};
+
+    const GRAPHICS: &Graphics = crate::include_aseprite!(
+        "../examples/the-purple-night/gfx/objects.aseprite",
+        "../examples/the-purple-night/gfx/boss.aseprite"
+    );
+
+    const BOSS: &Tag = GRAPHICS.tags().get("Boss");
+    const EMU: &Tag = GRAPHICS.tags().get("emu - idle");
+
+    const METASPRITE: MetaSprite = MetaSprite::new(&[
+        MetaSpritePart::new(BOSS.sprite(0), 0, 0),
+        MetaSpritePart::new(EMU.sprite(0), 40, 8),
+    ]);
+
+    const LARGER_METASPRITE: MetaSprite = MetaSprite::new(&[
+        MetaSpritePart::new(BOSS.sprite(0), 0, 0),
+        MetaSpritePart::new(EMU.sprite(0), 40, 8),
+        MetaSpritePart::new(EMU.sprite(1), 40, 40),
+    ]);
+
+    #[test_case]
+    fn metasprite_offsets_are_mirrored_when_flipped(_gba: &mut crate::Gba) {
+        let (boss_width, _) = BOSS.sprite(0).size().to_width_height();
+        let (emu_width, emu_height) = EMU.sprite(0).size().to_width_height();
+
+        let size = METASPRITE.size();
+        assert_eq!(size.x, (40 + emu_width as i32).max(boss_width as i32));
+
+        let emu_part = &METASPRITE.parts()[1];
+        assert_eq!(
+            METASPRITE.part_offset(emu_part, false, false),
+            (40, 8).into()
+        );
+        assert_eq!(
+            METASPRITE.part_offset(emu_part, true, true),
+            (
+                size.x - 40 - emu_width as i32,
+                size.y - 8 - emu_height as i32
+            )
+                .into()
+        );
+    }
+
+    #[test_case]
+    fn meta_object_usage(gba: &mut crate::Gba) {
+        let object = gba.display.object.get();
+
+        {
+            let mut meta_object = object.meta_object(&METASPRITE);
+            meta_object
+                .set_position((20, 20).into())
+                .set_hflip(true)
+                .set_priority(Priority::P1);
+
+            object.commit();
+
+            meta_object.hide();
+            object.commit();
+        }
+
+        object.commit();
+    }
+
+    #[test_case]
+    fn failing_to_set_metasprite_leaves_it_unchanged(gba: &mut crate::Gba) {
+        let object = gba.display.object.get();
+
+        let mut meta_object = object.meta_object(&METASPRITE);
+
+        let mut other_objects = Vec::new();
+        while let Some(other) = object.try_get_object(object.sprite(EMU.sprite(0))) {
+            other_objects.push(other);
+        }
+
+        assert!(meta_object.try_set_metasprite(&LARGER_METASPRITE).is_none());
+        assert!(core::ptr::eq(meta_object.metasprite, &METASPRITE));
+        assert_eq!(meta_object.objects.len(), METASPRITE.parts().len());
+
+        other_objects.clear();
+        meta_object.set_metasprite(&LARGER_METASPRITE);
+        assert_eq!(meta_object.objects.len(), LARGER_METASPRITE.parts().len());
+    }
+}
@@ -21,6 +21,20 @@ use crate::fixnum::Vector2D;
 use crate::hash_map::HashMap;
 
 use attributes::*;
+use multiplex::{Multiplexer, HARDWARE_OBJECTS, MULTIPLEXED_OBJECTS};
+
+mod animation;
+mod collision;
+mod dynamic;
+mod metasprite;
+mod multiplex;
+mod pool;
+
+pub use animation::{Animation, AnimationEvent};
+pub use collision::{CollisionMask, PlacedCollisionMask};
+pub use dynamic::DynamicSprite;
+pub use metasprite::{MetaObject, MetaSprite, MetaSpritePart};
+pub use pool::{ObjectPool, PoolId, PooledObject};
 
 static mut OBJECT_CONTROLLER: MaybeUninit<ObjectControllerStatic> = MaybeUninit::uninit();
 
@@ -114,6 +128,7 @@ pub struct Sprite {
     palette: &'static Palette16,
     data: &'static [u8],
     size: Size,
+    collision_mask: Option<&'static CollisionMask>,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -135,8 +150,25 @@ pub enum Size {
     S32x64 = 0b10_11,
 }
 
+/// Includes the frames and tags of one or more aseprite files as a [`Graphics`].
+///
+/// Starting the list of files with `collision_masks;` also generates a
+/// [`CollisionMask`] for every frame, which is available through
+/// [`Sprite::collision_mask`].
+///
+/// ```rust,ignore
+/// const GRAPHICS: &Graphics = agb::include_aseprite!(collision_masks; "gfx/bullets.aseprite");
+/// ```
 #[macro_export]
 macro_rules! include_aseprite {
+    (collision_masks; $($aseprite_path: expr),*) => {{
+        use $crate::display::object::{CollisionMask, Size, Sprite, Tag, TagMap, Graphics};
+        use $crate::display::palette16::Palette16;
+
+        $crate::include_aseprite_inner!(collision_masks; $($aseprite_path),*);
+
+        &Graphics::new(SPRITES, TAGS)
+    }};
     ($($aseprite_path: expr),*) => {{
         use $crate::display::object::{Size, Sprite, Tag, TagMap, Graphics};
         use $crate::display::palette16::Palette16;
@@ -147,6 +179,50 @@ macro_rules! include_aseprite {
     }};
 }
 
+/// Imports a png sprite sheet as a [`Graphics`], for use without aseprite.
+/// The sheet is split into frames of the given size going left to right, then
+/// top to bottom, and ranges of frames can be named to create tags.
+///
+/// ```toml
+/// version = "1.0"
+///
+/// filename = "player.png"
+/// transparent_colour = "ff00ff"
+/// frame_width = 16
+/// frame_height = 32
+/// frame_duration = 100 # milliseconds, defaults to 100
+/// collision_masks = false
+///
+/// [tags.walk]
+/// from = 0
+/// to = 3
+/// direction = "pingpong" # forward (default), backward or pingpong
+/// duration = 80 # overrides the frame duration for these frames
+/// ```
+///
+/// Tags can share frames, but tags which overlap can't set different durations.
+///
+/// Setting `collision_masks = true` generates a [`CollisionMask`] for every frame.
+///
+/// ```rust,ignore
+/// const GRAPHICS: &Graphics = agb::include_sprite_sheet!("gfx/player.toml");
+/// const WALK: &Tag = GRAPHICS.tags().get("walk");
+/// ```
+#[macro_export]
+macro_rules! include_sprite_sheet {
+    ($sprite_sheet_path: expr) => {{
+        // CollisionMask is only used if collision_masks is set in the toml file
+        #[allow(unused_imports)]
+        use $crate::display::object::CollisionMask;
+        use $crate::display::object::{Graphics, Size, Sprite, Tag, TagMap};
+        use $crate::display::palette16::Palette16;
+
+        $crate::include_sprite_sheet_inner!($sprite_sheet_path);
+
+        &Graphics::new(SPRITES, TAGS)
+    }};
+}
+
 pub struct Graphics {
     sprites: &'static [Sprite],
     tag_map: &'static TagMap,
@@ -232,19 +308,36 @@ impl Direction {
 
 pub struct Tag {
     sprites: *const Sprite,
+    durations: *const u16,
     len: usize,
     direction: Direction,
 }
 
 impl Tag {
-    pub fn sprites(&self) -> &'static [Sprite] {
+    pub const fn sprites(&self) -> &'static [Sprite] {
         unsafe { slice::from_raw_parts(self.sprites, self.len) }
     }
 
-    pub fn sprite(&self, idx: usize) -> &'static Sprite {
+    pub const fn sprite(&self, idx: usize) -> &'static Sprite {
         &self.sprites()[idx]
     }
 
+    /// The durations of each frame in this tag in milliseconds, as set in aseprite.
+    pub fn frame_durations(&self) -> &'static [u16] {
+        unsafe { slice::from_raw_parts(self.durations, self.len) }
+    }
+
+    /// The duration of the `idx`th frame of this tag in milliseconds.
+    pub fn frame_duration(&self, idx: usize) -> u16 {
+        self.frame_durations()[idx]
+    }
+
+    /// Creates a new [`Animation`] which plays this tag respecting the frame
+    /// durations and animation direction.
+    pub fn animation(&'static self) -> Animation {
+        Animation::new(self)
+    }
+
     #[inline]
     pub fn animation_sprite(&self, idx: usize) -> &'static Sprite {
         let len_sub_1 = self.len - 1;
@@ -259,11 +352,19 @@ impl Tag {
     }
 
     #[doc(hidden)]
-    pub const fn new(sprites: &'static [Sprite], from: usize, to: usize, direction: usize) -> Self {
+    pub const fn new(
+        sprites: &'static [Sprite],
+        durations: &'static [u16],
+        from: usize,
+        to: usize,
+        direction: usize,
+    ) -> Self {
         assert!(from <= to);
         assert!(to < sprites.len());
+        assert!(sprites.len() == durations.len());
         Self {
             sprites: &sprites[from] as *const Sprite,
+            durations: &durations[from] as *const u16,
             len: to - from + 1,
             direction: Direction::from_usize(direction),
         }
@@ -287,6 +388,9 @@ impl Size {
             Size::S32x64 => 32,
         }
     }
+    fn layout(self) -> Layout {
+        Layout::from_size_align(self.number_of_tiles() * BYTES_PER_TILE_4BPP, 8).unwrap()
+    }
     const fn shape_size(self) -> (u8, u8) {
         (self as u8 >> 2, self as u8 & 0b11)
     }
@@ -329,6 +433,8 @@ impl Size {
 
 pub struct SpriteBorrow<'a> {
     id: SpriteId,
+    size: Size,
+    palette: &'static Palette16,
     sprite_location: u16,
     palette_location: u16,
     phantom: ObjectControllerReference<'a>,
@@ -380,6 +486,10 @@ impl Attributes {
     }
 
     fn commit(&self, location: usize) {
+        write_oam_attributes(location, self.to_u16s());
+    }
+
+    fn to_u16s(&self) -> [u16; 3] {
         let mode = self.a0.object_mode();
         let attrs: [[u8; 2]; 3] = match mode {
             ObjectMode::Normal => [
@@ -394,17 +504,20 @@ impl Attributes {
             ],
         };
 
-        unsafe {
-            let attrs: [u16; 3] = core::mem::transmute(attrs);
-            let ptr = (OBJECT_ATTRIBUTE_MEMORY as *mut u16).add(location * 4);
-
-            ptr.add(0).write_volatile(attrs[0]);
-            ptr.add(1).write_volatile(attrs[1]);
-            ptr.add(2).write_volatile(attrs[2]);
-        };
+        unsafe { core::mem::transmute(attrs) }
     }
 }
 
+fn write_oam_attributes(location: usize, attrs: [u16; 3]) {
+    unsafe {
+        let ptr = (OBJECT_ATTRIBUTE_MEMORY as *mut u16).add(location * 4);
+
+        ptr.add(0).write_volatile(attrs[0]);
+        ptr.add(1).write_volatile(attrs[1]);
+        ptr.add(2).write_volatile(attrs[2]);
+    };
+}
+
 pub struct Object<'a> {
     loan: Loan<'a>,
 }
@@ -437,6 +550,8 @@ struct ObjectInner {
     sprite: SpriteBorrow<'static>,
     previous_sprite: SpriteBorrow<'static>,
     destroy: bool,
+    /// Whether the attributes or sprite have changed since the last commit
+    dirty: bool,
     z: i32,
 }
 
@@ -445,21 +560,40 @@ struct ObjectControllerStatic {
     free_object: Vec<u8>,
     shadow_oam: Vec<Option<ObjectInner>>,
     z_order: Vec<u8>,
+    /// Whether the z of any object has changed since the last commit
+    z_dirty: bool,
     sprite_controller: SpriteControllerInner,
+    multiplexer: Option<Multiplexer>,
+    flicker_frame: u8,
+    /// Whether the last multiplexing plan had to drop some objects, in which
+    /// case a new plan is needed every frame to change which are dropped
+    multiplex_flickering: bool,
 }
 
 impl ObjectControllerStatic {
     unsafe fn new() -> Self {
         Self {
-            shadow_oam: (0..128).map(|_| None).collect(),
-            z_order: (0..128).collect(),
-            free_object: (0..128).collect(),
+            shadow_oam: (0..HARDWARE_OBJECTS).map(|_| None).collect(),
+            z_order: (0..HARDWARE_OBJECTS as u8).collect(),
+            z_dirty: false,
+            free_object: (0..HARDWARE_OBJECTS as u8).collect(),
             _free_affine_matricies: (0..32).collect(),
             sprite_controller: SpriteControllerInner::new(),
+            multiplexer: None,
+            flicker_frame: 0,
+            multiplex_flickering: false,
         }
     }
 
-    fn update_z_ordering(&mut self) {
+    /// Sorts the objects by z if any have changed since the last commit,
+    /// returning whether the order of the objects is different as a result.
+    fn update_z_ordering(&mut self) -> bool {
+        if !core::mem::take(&mut self.z_dirty) {
+            return false;
+        }
+
+        let previous_order = self.z_order.clone();
+
         let shadow_oam = &self.shadow_oam;
         self.z_order.sort_by_key(|&a| {
             shadow_oam[a as usize]
@@ -467,6 +601,8 @@ impl ObjectControllerStatic {
                 .map(|s| s.z)
                 .unwrap_or(i32::MAX)
         });
+
+        previous_order != self.z_order
     }
 }
 
@@ -485,11 +621,21 @@ impl Drop for ObjectController {
 const HIDDEN_VALUE: u16 = 0b10 << 8;
 
 impl ObjectController {
+    /// Writes any changes to objects to OAM. Only objects which have changed
+    /// since the last commit are written, unless the z order has changed in
+    /// which case every object is.
     pub fn commit(&self) {
         let mut s = unsafe { get_object_controller(&self.phantom) };
 
         let s = &mut *s;
 
+        if s.multiplexer.is_some() {
+            s.commit_multiplexed();
+            return;
+        }
+
+        let z_order_changed = s.update_z_ordering();
+
         for (i, &z) in s.z_order.iter().enumerate() {
             if let Some(o) = &mut s.shadow_oam[z as usize] {
                 if o.destroy {
@@ -505,13 +651,19 @@ impl ObjectController {
                     a.previous_sprite.drop(&mut s.sprite_controller);
                     a.sprite.drop(&mut s.sprite_controller);
                 } else {
-                    o.attrs.commit(i);
+                    if o.dirty || z_order_changed {
+                        o.attrs.commit(i);
+                    }
+
+                    if o.dirty {
+                        o.dirty = false;
 
-                    let mut a = o.sprite.clone(&mut s.sprite_controller);
-                    core::mem::swap(&mut o.previous_sprite, &mut a);
-                    a.drop(&mut s.sprite_controller);
+                        let mut a = o.sprite.clone(&mut s.sprite_controller);
+                        core::mem::swap(&mut o.previous_sprite, &mut a);
+                        a.drop(&mut s.sprite_controller);
+                    }
                 }
-            } else {
+            } else if z_order_changed {
                 unsafe {
                     (OBJECT_ATTRIBUTE_MEMORY as *mut u16)
                         .add(i * 4)
@@ -540,6 +692,43 @@ impl ObjectController {
         }
     }
 
+    /// Allows up to 256 objects to be used at once rather than the 128 the
+    /// hardware supports. Objects are sorted by their y position, and hardware
+    /// slots are reused once the object using them has been drawn by rewriting
+    /// them in the vcount interrupt.
+    ///
+    /// If there are more than 128 objects on a single line, some are dropped,
+    /// with which ones changing every frame causing them to flicker. While there
+    /// are more than 128 visible objects, z ordering is not respected.
+    ///
+    /// This uses the vblank and vcount interrupts and lasts until the object
+    /// controller is dropped.
+    pub fn enable_multiplexing(&self) {
+        let mut s = unsafe { get_object_controller(&self.phantom) };
+
+        if s.multiplexer.is_some() {
+            return;
+        }
+
+        s.shadow_oam.resize_with(MULTIPLEXED_OBJECTS, || None);
+        s.z_order
+            .extend((HARDWARE_OBJECTS..MULTIPLEXED_OBJECTS).map(|i| i as u8));
+        s.free_object.splice(
+            0..0,
+            (HARDWARE_OBJECTS..MULTIPLEXED_OBJECTS).map(|i| i as u8),
+        );
+        s.z_dirty = true;
+
+        s.multiplexer = Some(Multiplexer::new());
+    }
+
+    /// The number of objects which can exist at once, which is higher while
+    /// multiplexing is enabled.
+    pub(super) fn object_capacity(&self) -> usize {
+        let s = unsafe { get_object_controller(&self.phantom) };
+        s.shadow_oam.len()
+    }
+
     pub fn object<'a>(&'a self, sprite: SpriteBorrow<'a>) -> Object<'a> {
         self.try_get_object(sprite).expect("No object available")
     }
@@ -550,7 +739,7 @@ impl ObjectController {
         let mut attrs = Attributes::new();
 
         attrs.a2.set_tile_index(sprite.sprite_location);
-        let shape_size = sprite.id.sprite().size.shape_size();
+        let shape_size = sprite.size.shape_size();
         attrs.a2.set_palete_bank(sprite.palette_location as u8);
         attrs.a0.set_shape(shape_size.0);
         attrs.a1a.set_size(shape_size.1);
@@ -565,6 +754,7 @@ impl ObjectController {
             z: 0,
             previous_sprite: new_sprite.clone(&mut s.sprite_controller),
             destroy: false,
+            dirty: true,
             sprite: new_sprite,
         });
 
@@ -573,7 +763,7 @@ impl ObjectController {
             phantom: PhantomData,
         };
 
-        s.update_z_ordering();
+        s.z_dirty = true;
 
         Some(Object { loan })
     }
@@ -591,6 +781,59 @@ impl ObjectController {
                 .try_get_sprite(sprite)
         }
     }
+
+    /// Copies the given sprites into sprite VRAM now rather than the first time
+    /// they are used, and keeps them there until the returned [`PinnedSprites`]
+    /// is dropped. This is useful for avoiding copying sprite data in the middle
+    /// of gameplay, for example by preloading everything a level needs while it
+    /// loads.
+    ///
+    /// ```rust,ignore
+    /// let pinned = object_controller.preload(GRAPHICS.sprites());
+    /// // borrowing sprites from GRAPHICS no longer copies any data
+    /// let sprite = object_controller.sprite(WALK.sprite(0));
+    /// ```
+    pub fn preload(&self, sprites: &'static [Sprite]) -> PinnedSprites<'_> {
+        self.try_preload(sprites)
+            .expect("Not enough sprite memory to preload sprites")
+    }
+
+    /// Like [`ObjectController::preload`], but returns `None` if there isn't
+    /// space for all the sprites. In this case none of the sprites are pinned.
+    pub fn try_preload(&self, sprites: &'static [Sprite]) -> Option<PinnedSprites<'_>> {
+        let borrows = sprites
+            .iter()
+            .map(|sprite| self.try_get_sprite(sprite))
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(PinnedSprites { borrows })
+    }
+}
+
+/// A set of sprites which are kept in sprite VRAM, created by
+/// [`ObjectController::preload`]. Dropping this releases the pin, after which
+/// sprites which are not used by any object are freed as usual.
+pub struct PinnedSprites<'a> {
+    borrows: Vec<SpriteBorrow<'a>>,
+}
+
+impl<'a> PinnedSprites<'a> {
+    /// Pins the given sprites as well as those already in this set.
+    pub fn extend(&mut self, other: PinnedSprites<'a>) {
+        self.borrows.extend(other.borrows);
+    }
+
+    /// The number of sprites in this set.
+    pub fn len(&self) -> usize {
+        self.borrows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.borrows.is_empty()
+    }
+
+    /// Releases the pin on the sprites, equivalent to dropping this.
+    pub fn release(self) {}
 }
 
 impl<'a> Object<'a> {
@@ -602,10 +845,20 @@ impl<'a> Object<'a> {
             .unwrap_unchecked()
     }
 
+    /// Gets the attributes of the object in order to change them, marking the
+    /// object as needing to be written to OAM in the next commit.
+    #[inline(always)]
+    unsafe fn attributes(&mut self) -> &mut Attributes {
+        let object_inner = self.object_inner();
+        object_inner.dirty = true;
+        &mut object_inner.attrs
+    }
+
     pub fn set_sprite(&'_ mut self, sprite: SpriteBorrow<'a>) {
         let object_inner = unsafe { self.object_inner() };
+        object_inner.dirty = true;
         object_inner.attrs.a2.set_tile_index(sprite.sprite_location);
-        let shape_size = sprite.id.sprite().size.shape_size();
+        let shape_size = sprite.size.shape_size();
         object_inner
             .attrs
             .a2
@@ -617,89 +870,85 @@ impl<'a> Object<'a> {
     }
 
     pub fn show(&mut self) -> &mut Self {
-        let object_inner = unsafe { self.object_inner() };
-        object_inner.attrs.a0.set_object_mode(ObjectMode::Normal);
+        let attrs = unsafe { self.attributes() };
+        attrs.a0.set_object_mode(ObjectMode::Normal);
+
+        self
+    }
 
+    /// Sets whether the object is drawn normally, semi-transparently or as part
+    /// of the object window mask.
+    pub fn set_mode(&mut self, mode: GraphicsMode) -> &mut Self {
+        let attrs = unsafe { self.attributes() };
+        attrs.a0.set_graphics_mode(mode);
         self
     }
 
     pub fn set_hflip(&mut self, flip: bool) -> &mut Self {
-        let object_inner = unsafe { self.object_inner() };
-        object_inner.attrs.a1s.set_horizontal_flip(flip);
+        let attrs = unsafe { self.attributes() };
+        attrs.a1s.set_horizontal_flip(flip);
         self
     }
 
     pub fn set_vflip(&mut self, flip: bool) -> &mut Self {
-        let object_inner = unsafe { self.object_inner() };
-        object_inner.attrs.a1s.set_vertical_flip(flip);
+        let attrs = unsafe { self.attributes() };
+        attrs.a1s.set_vertical_flip(flip);
         self
     }
 
     pub fn set_x(&mut self, x: u16) -> &mut Self {
-        let object_inner = unsafe { self.object_inner() };
-        object_inner.attrs.a1a.set_x(x.rem_euclid(1 << 9) as u16);
-        object_inner.attrs.a1s.set_x(x.rem_euclid(1 << 9) as u16);
+        let attrs = unsafe { self.attributes() };
+        attrs.a1a.set_x(x.rem_euclid(1 << 9) as u16);
+        attrs.a1s.set_x(x.rem_euclid(1 << 9) as u16);
         self
     }
 
     pub fn set_priority(&mut self, priority: Priority) -> &mut Self {
-        let object_inner = unsafe { self.object_inner() };
-        object_inner.attrs.a2.set_priority(priority);
+        let attrs = unsafe { self.attributes() };
+        attrs.a2.set_priority(priority);
         self
     }
 
     pub fn hide(&mut self) -> &mut Self {
-        let object_inner = unsafe { self.object_inner() };
-        object_inner.attrs.a0.set_object_mode(ObjectMode::Disabled);
+        let attrs = unsafe { self.attributes() };
+        attrs.a0.set_object_mode(ObjectMode::Disabled);
         self
     }
 
     pub fn set_y(&mut self, y: u16) -> &mut Self {
-        let object_inner = unsafe { self.object_inner() };
-        object_inner.attrs.a0.set_y(y as u8);
+        let attrs = unsafe { self.attributes() };
+        attrs.a0.set_y(y as u8);
 
         self
     }
 
     pub fn set_z(&mut self, z: i32) -> &mut Self {
         let object_inner = unsafe { self.object_inner() };
-        object_inner.z = z;
-        unsafe {
-            get_object_controller(&self.loan.phantom).update_z_ordering();
+        if object_inner.z != z {
+            object_inner.z = z;
+            unsafe {
+                get_object_controller(&self.loan.phantom).z_dirty = true;
+            }
         }
 
         self
     }
 
     pub fn set_position(&mut self, position: Vector2D<i32>) -> &mut Self {
-        let object_inner = unsafe { self.object_inner() };
-        object_inner.attrs.a0.set_y(position.y as u8);
-        object_inner
-            .attrs
-            .a1a
-            .set_x(position.x.rem_euclid(1 << 9) as u16);
-        object_inner
-            .attrs
-            .a1s
-            .set_x(position.x.rem_euclid(1 << 9) as u16);
+        let attrs = unsafe { self.attributes() };
+        attrs.a0.set_y(position.y as u8);
+        attrs.a1a.set_x(position.x.rem_euclid(1 << 9) as u16);
+        attrs.a1s.set_x(position.x.rem_euclid(1 << 9) as u16);
         self
     }
 }
 
 /// The Sprite Id is a thin wrapper around the pointer to the sprite in
-/// rom and is therefore a unique identifier to a sprite
+/// rom and is therefore a unique identifier to a sprite. Dynamic sprites use
+/// their location in sprite VRAM, which can't overlap with a static sprite.
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 struct SpriteId(usize);
 
-impl SpriteId {
-    fn sprite(self) -> &'static Sprite {
-        // # Safety
-        // This must be constructed using the id() of a sprite, so
-        // they are always valid and always static
-        unsafe { (self.0 as *const Sprite).as_ref().unwrap_unchecked() }
-    }
-}
-
 /// The palette id is a thin wrapper around the pointer to the palette in rom
 /// and is therefore a unique reference to a palette
 #[derive(Copy, Clone, PartialEq, Eq, Hash)]
@@ -718,19 +967,28 @@ impl Sprite {
     fn id(&'static self) -> SpriteId {
         SpriteId(self as *const _ as usize)
     }
-    fn layout(&self) -> Layout {
-        Layout::from_size_align(self.size.number_of_tiles() * BYTES_PER_TILE_4BPP, 8).unwrap()
-    }
     pub const fn new(palette: &'static Palette16, data: &'static [u8], size: Size) -> Self {
         Self {
             palette,
             data,
             size,
+            collision_mask: None,
+        }
+    }
+    #[doc(hidden)]
+    pub const fn with_collision_mask(self, collision_mask: &'static CollisionMask) -> Self {
+        Self {
+            collision_mask: Some(collision_mask),
+            ..self
         }
     }
     pub const fn size(&self) -> Size {
         self.size
     }
+    /// The collision mask of the sprite, if it was imported with collision masks.
+    pub const fn collision_mask(&self) -> Option<&'static CollisionMask> {
+        self.collision_mask
+    }
 }
 
 impl SpriteControllerInner {
@@ -742,6 +1000,8 @@ impl SpriteControllerInner {
             let palette_location = self.palette(sprite.palette).unwrap();
             Some(SpriteBorrow {
                 id,
+                size: sprite.size,
+                palette: sprite.palette,
                 palette_location,
                 sprite_location: location,
                 phantom: PhantomData,
@@ -749,13 +1009,13 @@ impl SpriteControllerInner {
         } else {
             // layout is non zero sized, so this is safe to call
 
-            let dest = unsafe { SPRITE_ALLOCATOR.alloc(sprite.layout())? };
+            let dest = unsafe { SPRITE_ALLOCATOR.alloc(sprite.size.layout())? };
 
             let palette_location = self.palette(sprite.palette);
             let palette_location = match palette_location {
                 Some(a) => a,
                 None => {
-                    unsafe { SPRITE_ALLOCATOR.dealloc(dest.as_ptr(), sprite.layout()) }
+                    unsafe { SPRITE_ALLOCATOR.dealloc(dest.as_ptr(), sprite.size.layout()) }
                     return None;
                 }
             };
@@ -773,6 +1033,8 @@ impl SpriteControllerInner {
 
             Some(SpriteBorrow {
                 id,
+                size: sprite.size,
+                palette: sprite.palette,
                 palette_location,
                 sprite_location: storage.location,
                 phantom: PhantomData,
@@ -811,19 +1073,19 @@ impl SpriteControllerInner {
         }
     }
 
-    fn return_sprite(&mut self, sprite: &'static Sprite) {
-        let storage = self.sprite.get_mut(&sprite.id());
+    fn return_sprite(&mut self, id: SpriteId, size: Size, palette: &'static Palette16) {
+        let storage = self.sprite.get_mut(&id);
 
         if let Some(storage) = storage {
             storage.count -= 1;
 
             if storage.count == 0 {
-                unsafe { SPRITE_ALLOCATOR.dealloc(storage.as_sprite_ptr(), sprite.layout()) };
-                self.sprite.remove(&sprite.id());
+                unsafe { SPRITE_ALLOCATOR.dealloc(storage.as_sprite_ptr(), size.layout()) };
+                self.sprite.remove(&id);
             }
         }
 
-        self.return_palette(sprite.palette)
+        self.return_palette(palette)
     }
 
     fn return_palette(&mut self, palette: &'static Palette16) {
@@ -843,21 +1105,24 @@ impl SpriteControllerInner {
 impl<'a> Drop for SpriteBorrow<'a> {
     fn drop(&mut self) {
         let mut s = unsafe { get_object_controller(&self.phantom) };
-        s.sprite_controller.return_sprite(self.id.sprite())
+        s.sprite_controller
+            .return_sprite(self.id, self.size, self.palette)
     }
 }
 
 impl<'a> SpriteBorrow<'a> {
     fn drop(self, s: &mut SpriteControllerInner) {
-        s.return_sprite(self.id.sprite());
+        s.return_sprite(self.id, self.size, self.palette);
         core::mem::forget(self);
     }
 
     fn clone(&self, s: &mut SpriteControllerInner) -> Self {
         s.sprite.entry(self.id).and_modify(|a| a.count += 1);
-        let _ = s.palette(self.id.sprite().palette).unwrap();
+        let _ = s.palette(self.palette).unwrap();
         Self {
             id: self.id,
+            size: self.size,
+            palette: self.palette,
             sprite_location: self.sprite_location,
             palette_location: self.palette_location,
             phantom: PhantomData,
@@ -880,12 +1145,18 @@ enum ObjectMode {
     AffineDouble,
 }
 
-#[derive(BitfieldSpecifier, Clone, Copy)]
+/// How an object is drawn, set using [`Object::set_mode`].
+#[derive(BitfieldSpecifier, Clone, Copy, Debug, PartialEq, Eq)]
 #[bits = 2]
-enum GraphicsMode {
+pub enum GraphicsMode {
+    /// The object is drawn as normal.
     Normal,
-    AlphaBlending,
-    Window,
+    /// The object is alpha blended with the layers beneath it, using the
+    /// weights and second target layers set in [`Blend`][crate::display::blend::Blend].
+    SemiTransparent,
+    /// The object isn't drawn, and instead the pixels it covers form the object
+    /// window, see [`Window`][crate::display::window::Window].
+    ObjectWindow,
 }
 
 #[derive(BitfieldSpecifier, Clone, Copy)]
@@ -977,8 +1248,106 @@ mod tests {
             x.set_sprite(object.sprite(BOSS.sprite(2)));
 
             object.commit();
+
+            let y = objects[1].as_mut().unwrap();
+            y.set_mode(GraphicsMode::SemiTransparent);
+            object.commit();
+
+            y.set_mode(GraphicsMode::ObjectWindow);
+            object.commit();
+        }
+
+        object.commit();
+    }
+
+    #[test_case]
+    fn commit_only_writes_changed_objects(gba: &mut crate::Gba) {
+        const GRAPHICS: &Graphics =
+            include_aseprite!("../examples/the-purple-night/gfx/objects.aseprite");
+        const EMU: &Tag = GRAPHICS.tags().get("emu - idle");
+
+        // attribute 2 of the first object in OAM
+        let attribute_2 = (OBJECT_ATTRIBUTE_MEMORY + 4) as *mut u16;
+
+        let object = gba.display.object.get();
+
+        {
+            let mut emu = object.object(object.sprite(EMU.sprite(0)));
+            emu.set_position((10, 10).into()).show();
+            object.commit();
+
+            let committed = unsafe { attribute_2.read_volatile() };
+            unsafe { attribute_2.write_volatile(0x1234) };
+
+            object.commit();
+            assert_eq!(
+                unsafe { attribute_2.read_volatile() },
+                0x1234,
+                "unchanged objects shouldn't be written"
+            );
+
+            emu.set_z(5);
+            object.commit();
+            assert_eq!(
+                unsafe { attribute_2.read_volatile() },
+                0x1234,
+                "objects shouldn't be written if the z order is the same"
+            );
+
+            emu.set_position((20, 20).into());
+            object.commit();
+            assert_eq!(unsafe { attribute_2.read_volatile() }, committed);
+        }
+
+        object.commit();
+    }
+
+    #[test_case]
+    fn sprite_sheet_usage(gba: &mut crate::Gba) {
+        const GRAPHICS: &Graphics = include_sprite_sheet!("gfx/test_sprite_sheet.toml");
+
+        const SPIN: &Tag = GRAPHICS.tags().get("spin");
+        const STILL: &Tag = GRAPHICS.tags().get("still");
+
+        assert_eq!(GRAPHICS.sprites().len(), 4);
+        assert_eq!(SPIN.sprites().len(), 4);
+        assert_eq!(SPIN.frame_durations(), &[50, 50, 50, 50]);
+        assert!(core::ptr::eq(STILL.sprite(0), SPIN.sprite(2)));
+        assert!(STILL.sprite(0).size() == Size::S16x16);
+
+        let object = gba.display.object.get();
+
+        {
+            let mut sprite = object.object(object.sprite(SPIN.animation_sprite(5)));
+            sprite.set_position((50, 50).into()).show();
+
+            object.commit();
         }
 
         object.commit();
     }
+
+    #[test_case]
+    fn preloaded_sprites_stay_in_vram(gba: &mut crate::Gba) {
+        const GRAPHICS: &Graphics = include_sprite_sheet!("gfx/test_sprite_sheet.toml");
+        let sprite = GRAPHICS.tags().get("still").sprite(0);
+
+        let is_loaded = || {
+            let s = unsafe { get_object_controller(&PhantomData) };
+            s.sprite_controller.sprite.contains_key(&sprite.id())
+        };
+
+        let object = gba.display.object.get();
+
+        let pinned = object.preload(GRAPHICS.sprites());
+        assert_eq!(pinned.len(), 4);
+        assert!(is_loaded());
+
+        let location = object.sprite(sprite).sprite_location;
+        assert!(is_loaded(), "pinned sprites shouldn't be freed");
+        assert_eq!(object.sprite(sprite).sprite_location, location);
+
+        pinned.release();
+        assert!(!is_loaded());
+    }
 }
@@ -0,0 +1,413 @@
+//! Allows more than 128 objects on screen at once by reusing hardware object
+//! slots further down the screen. The slots are rewritten in the vcount
+//! interrupt once the object previously using them has finished being drawn.
+
+use alloc::collections::BinaryHeap;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::cmp::Reverse;
+
+use bare_metal::Mutex;
+
+use super::{ObjectControllerStatic, ObjectInner, ObjectMode, HIDDEN_VALUE};
+use crate::display::{DISPLAY_STATUS, HEIGHT, VCOUNT, WIDTH};
+use crate::interrupt::{add_interrupt_handler, free, Interrupt, InterruptHandler};
+
+/// The number of hardware object slots in OAM.
+pub(super) const HARDWARE_OBJECTS: usize = 128;
+
+/// The number of objects which can exist when multiplexing. This is limited by
+/// objects being referred to by a `u8` index.
+pub(super) const MULTIPLEXED_OBJECTS: usize = 256;
+
+/// The number of lines which must pass between an object finishing and the next
+/// object in the same slot starting. The hardware works out which objects are
+/// on a line during the previous line, and the rewrite needs some time to happen.
+const LINES_BETWEEN_REUSE: i32 = 2;
+
+static PLAN: Mutex<RefCell<Option<Plan>>> = Mutex::new(RefCell::new(None));
+
+/// Keeps the interrupts which apply the multiplexing plan alive.
+pub(super) struct Multiplexer {
+    _vblank: InterruptHandler<'static>,
+    _vcount: InterruptHandler<'static>,
+}
+
+impl Multiplexer {
+    pub(super) fn new() -> Self {
+        let vblank = add_interrupt_handler(Interrupt::VBlank, |key| {
+            if let Some(plan) = PLAN.borrow(*key).borrow_mut().as_mut() {
+                plan.restore();
+            }
+        });
+
+        let vcount = add_interrupt_handler(Interrupt::VCounter, |key| {
+            if let Some(plan) = PLAN.borrow(*key).borrow_mut().as_mut() {
+                plan.rewrite();
+            }
+        });
+
+        Self {
+            _vblank: vblank,
+            _vcount: vcount,
+        }
+    }
+}
+
+impl Drop for Multiplexer {
+    fn drop(&mut self) {
+        free(|key| PLAN.borrow(*key).replace(None));
+    }
+}
+
+impl ObjectControllerStatic {
+    pub(super) fn commit_multiplexed(&mut self) {
+        let mut changed = self.update_z_ordering() || self.multiplex_flickering;
+        let mut candidates = Vec::new();
+
+        for &z in self.z_order.iter() {
+            if let Some(o) = &mut self.shadow_oam[z as usize] {
+                if o.destroy {
+                    changed = true;
+                    self.free_object.push(z);
+
+                    let a = unsafe { self.shadow_oam[z as usize].take().unwrap_unchecked() };
+                    a.previous_sprite.drop(&mut self.sprite_controller);
+                    a.sprite.drop(&mut self.sprite_controller);
+                } else {
+                    if o.dirty {
+                        changed = true;
+                        o.dirty = false;
+
+                        let mut a = o.sprite.clone(&mut self.sprite_controller);
+                        core::mem::swap(&mut o.previous_sprite, &mut a);
+                        a.drop(&mut self.sprite_controller);
+                    }
+
+                    // rotating the rank each frame changes which objects are
+                    // dropped when there are too many on a line, causing them
+                    // to flicker rather than some never being displayed.
+                    candidates.extend(Candidate::new(o, z.wrapping_add(self.flicker_frame)));
+                }
+            }
+        }
+
+        // the current plan is still valid, and the vblank interrupt will keep
+        // putting it back in to OAM
+        if !changed {
+            return;
+        }
+
+        self.flicker_frame = self.flicker_frame.wrapping_add(1);
+
+        let plan = Plan::new(candidates);
+        self.multiplex_flickering = plan.flickering;
+
+        free(|key| {
+            PLAN.borrow(*key).borrow_mut().insert(plan).restore();
+        });
+    }
+}
+
+/// A visible object which is waiting to be assigned a hardware slot.
+struct Candidate {
+    top: i32,
+    bottom: i32,
+    attrs: [u16; 3],
+    rank: u8,
+}
+
+impl Candidate {
+    fn new(object: &ObjectInner, rank: u8) -> Option<Self> {
+        let attrs = &object.attrs;
+
+        let (width, height) = object.sprite.size.to_width_height();
+        let (width, height) = match attrs.a0.object_mode() {
+            ObjectMode::Disabled => return None,
+            ObjectMode::AffineDouble => (width as i32 * 2, height as i32 * 2),
+            _ => (width as i32, height as i32),
+        };
+
+        // the y coordinate is 8 bits and the x coordinate 9 bits, both of which
+        // wrap around to the other side of the screen
+        let top = match attrs.a0.y() as i32 {
+            y if y >= HEIGHT => y - 256,
+            y => y,
+        };
+        let left = match attrs.a1s.x() as i32 {
+            x if x >= WIDTH => x - 512,
+            x => x,
+        };
+
+        if top + height <= 0 || left + width <= 0 {
+            return None;
+        }
+
+        Some(Self {
+            top,
+            bottom: top + height,
+            attrs: attrs.to_u16s(),
+            rank,
+        })
+    }
+}
+
+struct Rewrite {
+    line: u8,
+    slot: u8,
+    attrs: [u16; 3],
+}
+
+/// Where every visible object goes in OAM, along with the slots which need
+/// rewriting part way through the frame.
+struct Plan {
+    initial: Vec<[u16; 3]>,
+    rewrites: Vec<Rewrite>,
+    next_rewrite: usize,
+    /// Whether some objects had to be dropped to fit them in
+    flickering: bool,
+}
+
+struct Assignment {
+    slot: u8,
+    line: Option<u8>,
+    attrs: [u16; 3],
+    rank: u8,
+}
+
+struct Slot {
+    /// The first line which an object in this slot could start on
+    free_from: i32,
+    assignment: Option<usize>,
+}
+
+impl Plan {
+    fn new(mut candidates: Vec<Candidate>) -> Self {
+        let hidden = [HIDDEN_VALUE, 0, 0];
+
+        // Everything fits, so there is no need to rewrite anything and the z
+        // order of the objects can be kept.
+        if candidates.len() <= HARDWARE_OBJECTS {
+            let mut initial: Vec<_> = candidates.iter().map(|c| c.attrs).collect();
+            initial.resize(HARDWARE_OBJECTS, hidden);
+
+            return Self {
+                initial,
+                rewrites: Vec::new(),
+                next_rewrite: 0,
+                flickering: false,
+            };
+        }
+
+        candidates.sort_by_key(|c| c.top);
+
+        let mut slots: Vec<Slot> = (0..HARDWARE_OBJECTS)
+            .map(|_| Slot {
+                free_from: i32::MIN,
+                assignment: None,
+            })
+            .collect();
+        // contains the slots ordered by when they are free, and can contain out
+        // of date entries which are ignored.
+        let mut free_slots: BinaryHeap<Reverse<(i32, u8)>> = (0..HARDWARE_OBJECTS)
+            .map(|slot| Reverse((i32::MIN, slot as u8)))
+            .collect();
+        let mut assignments: Vec<Assignment> = Vec::with_capacity(candidates.len());
+        let mut flickering = false;
+
+        for candidate in candidates {
+            let (free_from, slot) = loop {
+                let Reverse((free_from, slot)) =
+                    *free_slots.peek().expect("every slot should be in the heap");
+                if slots[slot as usize].free_from == free_from {
+                    break (free_from, slot);
+                }
+                free_slots.pop();
+            };
+
+            let slot_state = &mut slots[slot as usize];
+
+            if free_from <= candidate.top {
+                free_slots.pop();
+
+                let line = slot_state
+                    .assignment
+                    .map(|_| (free_from - LINES_BETWEEN_REUSE) as u8);
+
+                slot_state.free_from = candidate.bottom + LINES_BETWEEN_REUSE;
+                slot_state.assignment = Some(assignments.len());
+                free_slots.push(Reverse((slot_state.free_from, slot)));
+
+                assignments.push(Assignment {
+                    slot,
+                    line,
+                    attrs: candidate.attrs,
+                    rank: candidate.rank,
+                });
+
+                continue;
+            }
+
+            // Every slot is in use on this line, so drop whichever of the
+            // objects on the line has the lowest rank.
+            let (victim_slot, victim) = slots
+                .iter()
+                .enumerate()
+                .filter_map(|(i, slot)| Some((i, slot.assignment?)))
+                .min_by_key(|&(_, assignment)| assignments[assignment].rank)
+                .expect("every slot should be in use");
+
+            flickering = true;
+
+            if assignments[victim].rank > candidate.rank {
+                continue;
+            }
+
+            // The candidate starts after the victim, so it can be written at the
+            // same time the victim would have been.
+            assignments[victim].attrs = candidate.attrs;
+            assignments[victim].rank = candidate.rank;
+
+            let victim_slot_state = &mut slots[victim_slot];
+            let free_from = candidate.bottom + LINES_BETWEEN_REUSE;
+            if victim_slot_state.free_from != free_from {
+                victim_slot_state.free_from = free_from;
+                free_slots.push(Reverse((free_from, victim_slot as u8)));
+            }
+        }
+
+        let mut initial = alloc::vec![hidden; HARDWARE_OBJECTS];
+        let mut rewrites = Vec::new();
+
+        for assignment in assignments {
+            match assignment.line {
+                None => initial[assignment.slot as usize] = assignment.attrs,
+                Some(line) => rewrites.push(Rewrite {
+                    line,
+                    slot: assignment.slot,
+                    attrs: assignment.attrs,
+                }),
+            }
+        }
+
+        rewrites.sort_by_key(|rewrite| rewrite.line);
+
+        Self {
+            initial,
+            rewrites,
+            next_rewrite: 0,
+            flickering,
+        }
+    }
+
+    /// Writes the objects at the top of the screen in to OAM, ready for the
+    /// next frame to be drawn.
+    fn restore(&mut self) {
+        for (slot, attrs) in self.initial.iter().enumerate() {
+            super::write_oam_attributes(slot, *attrs);
+        }
+
+        self.next_rewrite = 0;
+        self.set_next_line();
+    }
+
+    /// Rewrites all the slots which are due to be rewritten by the current line.
+    fn rewrite(&mut self) {
+        let current_line = VCOUNT.get();
+
+        while let Some(rewrite) = self.rewrites.get(self.next_rewrite) {
+            if rewrite.line as u16 > current_line {
+                break;
+            }
+
+            super::write_oam_attributes(rewrite.slot as usize, rewrite.attrs);
+            self.next_rewrite += 1;
+        }
+
+        self.set_next_line();
+    }
+
+    fn set_next_line(&self) {
+        if let Some(rewrite) = self.rewrites.get(self.next_rewrite) {
+            DISPLAY_STATUS.set_bits(rewrite.line as u16, 8, 8);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidates(tops: impl Iterator<Item = i32>) -> Vec<Candidate> {
+        tops.enumerate()
+            .map(|(i, top)| Candidate {
+                top,
+                bottom: top + 8,
+                attrs: [i as u16, 0, 0],
+                rank: i as u8,
+            })
+            .collect()
+    }
+
+    #[test_case]
+    fn multiplexing_reuses_slots_further_down_the_screen(_gba: &mut crate::Gba) {
+        let plan = Plan::new(candidates(
+            core::iter::repeat_n(0, 100).chain(core::iter::repeat_n(20, 100)),
+        ));
+
+        assert!(plan.initial.iter().all(|attrs| attrs[0] != HIDDEN_VALUE));
+        assert_eq!(plan.rewrites.len(), 200 - HARDWARE_OBJECTS);
+        assert!(plan.rewrites.iter().all(|rewrite| rewrite.line == 8));
+        // the rewritten slots must be ones used by objects at the top
+        assert!(plan
+            .rewrites
+            .iter()
+            .all(|rewrite| plan.initial[rewrite.slot as usize][0] < 100));
+    }
+
+    #[test_case]
+    fn multiplexing_drops_lowest_ranked_objects_when_over_budget(_gba: &mut crate::Gba) {
+        let plan = Plan::new(candidates(core::iter::repeat_n(10, 130)));
+
+        assert!(plan.rewrites.is_empty());
+        assert!(plan.flickering);
+
+        let mut displayed: Vec<_> = plan.initial.iter().map(|attrs| attrs[0]).collect();
+        displayed.sort_unstable();
+        assert_eq!(displayed, (2..130).collect::<Vec<u16>>());
+    }
+
+    #[test_case]
+    fn multiplexing_objects(gba: &mut crate::Gba) {
+        use crate::display::object::{Graphics, Tag};
+
+        const GRAPHICS: &Graphics =
+            crate::include_aseprite!("../examples/the-purple-night/gfx/objects.aseprite");
+        const EMU: &Tag = GRAPHICS.tags().get("emu - idle");
+
+        let object = gba.display.object.get();
+        object.enable_multiplexing();
+
+        let vblank = crate::interrupt::VBlank::get();
+
+        {
+            let objects: Vec<_> = (0..200)
+                .map(|i| {
+                    let mut obj = object.object(object.sprite(EMU.sprite(0)));
+                    obj.set_position(((i % 10) * 20, (i / 10) * 8).into())
+                        .show();
+                    obj
+                })
+                .collect();
+
+            for _ in 0..3 {
+                vblank.wait_for_vblank();
+                object.commit();
+            }
+
+            drop(objects);
+        }
+
+        object.commit();
+    }
+}
@@ -0,0 +1,418 @@
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+
+use super::{Object, ObjectController, Sprite};
+use crate::display::{Priority, HEIGHT, WIDTH};
+use crate::fixnum::{Rect, Vector2D};
+
+/// Refers to an object added to an [`ObjectPool`]. This is invalid once the
+/// object is removed from the pool.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolId(usize);
+
+/// The settings of an object in an [`ObjectPool`]. Changes take effect the next
+/// time [`ObjectPool::update`] is called.
+#[derive(Clone, Copy)]
+pub struct PooledObject {
+    sprite: &'static Sprite,
+    position: Vector2D<i32>,
+    hflip: bool,
+    vflip: bool,
+    priority: Priority,
+    z: i32,
+    importance: i32,
+    visible: bool,
+}
+
+impl PooledObject {
+    fn new(sprite: &'static Sprite) -> Self {
+        Self {
+            sprite,
+            position: (0, 0).into(),
+            hflip: false,
+            vflip: false,
+            priority: Priority::P0,
+            z: 0,
+            importance: 0,
+            visible: true,
+        }
+    }
+
+    pub fn set_sprite(&mut self, sprite: &'static Sprite) -> &mut Self {
+        self.sprite = sprite;
+        self
+    }
+
+    /// Sets the position of the object in the world, which is relative to the
+    /// camera of the pool.
+    pub fn set_position(&mut self, position: Vector2D<i32>) -> &mut Self {
+        self.position = position;
+        self
+    }
+
+    pub fn position(&self) -> Vector2D<i32> {
+        self.position
+    }
+
+    pub fn set_hflip(&mut self, flip: bool) -> &mut Self {
+        self.hflip = flip;
+        self
+    }
+
+    pub fn set_vflip(&mut self, flip: bool) -> &mut Self {
+        self.vflip = flip;
+        self
+    }
+
+    pub fn set_priority(&mut self, priority: Priority) -> &mut Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn set_z(&mut self, z: i32) -> &mut Self {
+        self.z = z;
+        self
+    }
+
+    /// When there are more objects on screen than the pool can display, the
+    /// objects with the highest importance are displayed.
+    pub fn set_importance(&mut self, importance: i32) -> &mut Self {
+        self.importance = importance;
+        self
+    }
+
+    pub fn show(&mut self) -> &mut Self {
+        self.visible = true;
+        self
+    }
+
+    pub fn hide(&mut self) -> &mut Self {
+        self.visible = false;
+        self
+    }
+
+    fn screen_rect(&self, camera: Vector2D<i32>) -> Rect<i32> {
+        let (width, height) = self.sprite.size().to_width_height();
+        Rect::new(self.position - camera, (width as i32, height as i32).into())
+    }
+}
+
+impl PartialEq for PooledObject {
+    fn eq(&self, other: &Self) -> bool {
+        core::ptr::eq(self.sprite, other.sprite)
+            && self.position == other.position
+            && self.hflip == other.hflip
+            && self.vflip == other.vflip
+            && self.priority == other.priority
+            && self.z == other.z
+            && self.importance == other.importance
+            && self.visible == other.visible
+    }
+}
+
+struct Assigned<'a> {
+    object: Object<'a>,
+    /// The settings and camera last written to the object
+    applied: Option<(PooledObject, Vector2D<i32>)>,
+}
+
+struct Entry<'a> {
+    settings: PooledObject,
+    assigned: Option<Assigned<'a>>,
+}
+
+/// Manages objects which have positions in the world rather than on screen.
+/// Only objects within view of the camera use a hardware object, and if there
+/// are more than the pool is allowed to use then the most important ones are
+/// displayed.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// let mut pool = ObjectPool::new(&object_controller);
+/// let bullet = pool.add(BULLET.sprite(0));
+/// pool.get_mut(bullet).set_position((500, 20).into());
+///
+/// loop {
+///     pool.set_camera(player_position - (120, 80).into());
+///     pool.update();
+///
+///     vblank.wait_for_vblank();
+///     object_controller.commit();
+/// }
+/// ```
+pub struct ObjectPool<'a> {
+    controller: &'a ObjectController,
+    entries: Vec<Option<Entry<'a>>>,
+    free_entries: Vec<usize>,
+    camera: Vector2D<i32>,
+    max_objects: Option<usize>,
+}
+
+impl<'a> ObjectPool<'a> {
+    /// Creates a pool which can use every object the controller has, including
+    /// the extra ones available once multiplexing is enabled.
+    pub fn new(controller: &'a ObjectController) -> Self {
+        Self {
+            controller,
+            entries: Vec::new(),
+            free_entries: Vec::new(),
+            camera: (0, 0).into(),
+            max_objects: None,
+        }
+    }
+
+    /// Creates a pool which uses at most `max_objects` objects, leaving the rest
+    /// available to be used outside of the pool.
+    pub fn with_max_objects(controller: &'a ObjectController, max_objects: usize) -> Self {
+        Self {
+            max_objects: Some(max_objects),
+            ..Self::new(controller)
+        }
+    }
+
+    pub fn add(&mut self, sprite: &'static Sprite) -> PoolId {
+        let entry = Entry {
+            settings: PooledObject::new(sprite),
+            assigned: None,
+        };
+
+        if let Some(index) = self.free_entries.pop() {
+            self.entries[index] = Some(entry);
+            PoolId(index)
+        } else {
+            self.entries.push(Some(entry));
+            PoolId(self.entries.len() - 1)
+        }
+    }
+
+    /// Removes the object from the pool, freeing its hardware object if it had one.
+    pub fn remove(&mut self, id: PoolId) {
+        self.entries[id.0]
+            .take()
+            .expect("Object has already been removed from the pool");
+        self.free_entries.push(id.0);
+    }
+
+    pub fn get(&self, id: PoolId) -> &PooledObject {
+        &self.entry(id).settings
+    }
+
+    pub fn get_mut(&mut self, id: PoolId) -> &mut PooledObject {
+        &mut self.entries[id.0]
+            .as_mut()
+            .expect("Object has been removed from the pool")
+            .settings
+    }
+
+    /// Whether the object was given a hardware object in the last update.
+    pub fn is_displayed(&self, id: PoolId) -> bool {
+        self.entry(id).assigned.is_some()
+    }
+
+    /// Sets the position of the top left of the screen in the world.
+    pub fn set_camera(&mut self, camera: Vector2D<i32>) {
+        self.camera = camera;
+    }
+
+    pub fn camera(&self) -> Vector2D<i32> {
+        self.camera
+    }
+
+    fn entry(&self, id: PoolId) -> &Entry<'a> {
+        self.entries[id.0]
+            .as_ref()
+            .expect("Object has been removed from the pool")
+    }
+
+    /// Culls objects which are off screen and assigns hardware objects to the
+    /// most important objects which are on screen. Objects whose sprite can't be
+    /// loaded because sprite memory is full are culled too. This should be
+    /// called before committing the object controller.
+    pub fn update(&mut self) {
+        let controller = self.controller;
+        let camera = self.camera;
+        let screen = Rect::new((0, 0).into(), (WIDTH, HEIGHT).into());
+
+        let mut on_screen: Vec<usize> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(i, entry)| {
+                let settings = &entry.as_ref()?.settings;
+                (settings.visible && settings.screen_rect(camera).touches(screen.clone()))
+                    .then_some(i)
+            })
+            .collect();
+
+        let max_objects = self
+            .max_objects
+            .unwrap_or_else(|| controller.object_capacity());
+
+        if on_screen.len() > max_objects {
+            on_screen.sort_by_key(|&i| {
+                Reverse(
+                    self.entries[i]
+                        .as_ref()
+                        .map_or(0, |entry| entry.settings.importance),
+                )
+            });
+            on_screen.truncate(max_objects);
+        }
+
+        let mut displayed = alloc::vec![false; self.entries.len()];
+        for &i in on_screen.iter() {
+            displayed[i] = true;
+        }
+
+        // Objects which are no longer displayed give up their hardware objects
+        // to be reused by newly displayed ones.
+        let mut spare_objects: Vec<Object<'a>> = self
+            .entries
+            .iter_mut()
+            .zip(displayed.iter())
+            .filter(|(_, &displayed)| !displayed)
+            .filter_map(|(entry, _)| Some(entry.as_mut()?.assigned.take()?.object))
+            .collect();
+
+        for i in on_screen {
+            let entry = self.entries[i].as_mut().unwrap();
+
+            if entry.assigned.is_none() {
+                let object = controller
+                    .try_get_sprite(entry.settings.sprite)
+                    .and_then(|sprite| match spare_objects.pop() {
+                        Some(mut object) => {
+                            object.set_sprite(sprite);
+                            Some(object)
+                        }
+                        None => controller.try_get_object(sprite),
+                    });
+
+                entry.assigned = object.map(|object| Assigned {
+                    object,
+                    applied: None,
+                });
+            }
+
+            if let Some(assigned) = &mut entry.assigned {
+                if !assigned.apply(&entry.settings, camera, controller) {
+                    entry.assigned = None;
+                }
+            }
+        }
+    }
+}
+
+impl<'a> Assigned<'a> {
+    /// Updates the object to match the settings, returning false if its new
+    /// sprite couldn't be loaded.
+    fn apply(
+        &mut self,
+        settings: &PooledObject,
+        camera: Vector2D<i32>,
+        controller: &'a ObjectController,
+    ) -> bool {
+        if self.applied == Some((*settings, camera)) {
+            return true;
+        }
+
+        let sprite_changed = self
+            .applied
+            .is_some_and(|(applied, _)| !core::ptr::eq(applied.sprite, settings.sprite));
+        if sprite_changed {
+            match controller.try_get_sprite(settings.sprite) {
+                Some(sprite) => self.object.set_sprite(sprite),
+                None => return false,
+            }
+        }
+
+        self.object
+            .set_position(settings.position - camera)
+            .set_hflip(settings.hflip)
+            .set_vflip(settings.vflip)
+            .set_priority(settings.priority)
+            .set_z(settings.z)
+            .show();
+
+        self.applied = Some((*settings, camera));
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::object::{Graphics, Tag};
+
+    const GRAPHICS: &Graphics =
+        crate::include_aseprite!("../examples/the-purple-night/gfx/objects.aseprite");
+    const EMU: &Tag = GRAPHICS.tags().get("emu - idle");
+
+    #[test_case]
+    fn pool_culls_objects_off_screen(gba: &mut crate::Gba) {
+        let controller = gba.display.object.get();
+
+        {
+            let mut pool = ObjectPool::new(&controller);
+
+            let on_screen = pool.add(EMU.sprite(0));
+            pool.get_mut(on_screen).set_position((300, 100).into());
+
+            let off_screen = pool.add(EMU.sprite(0));
+            pool.get_mut(off_screen).set_position((1000, 100).into());
+
+            pool.set_camera((200, 0).into());
+            pool.update();
+            controller.commit();
+
+            assert!(pool.is_displayed(on_screen));
+            assert!(!pool.is_displayed(off_screen));
+
+            pool.set_camera((900, 0).into());
+            pool.update();
+            controller.commit();
+
+            assert!(!pool.is_displayed(on_screen));
+            assert!(pool.is_displayed(off_screen));
+        }
+
+        controller.commit();
+    }
+
+    #[test_case]
+    fn pool_displays_most_important_objects(gba: &mut crate::Gba) {
+        let controller = gba.display.object.get();
+
+        {
+            let mut pool = ObjectPool::with_max_objects(&controller, 2);
+
+            let ids: Vec<_> = [0, 10, 5]
+                .iter()
+                .map(|&importance| {
+                    let id = pool.add(EMU.sprite(0));
+                    pool.get_mut(id)
+                        .set_position((50, 50).into())
+                        .set_importance(importance);
+                    id
+                })
+                .collect();
+
+            pool.update();
+            controller.commit();
+
+            assert!(!pool.is_displayed(ids[0]));
+            assert!(pool.is_displayed(ids[1]));
+            assert!(pool.is_displayed(ids[2]));
+
+            pool.remove(ids[1]);
+            pool.update();
+            controller.commit();
+
+            assert!(pool.is_displayed(ids[0]));
+            assert!(pool.is_displayed(ids[2]));
+        }
+
+        controller.commit();
+    }
+}
@@ -0,0 +1,258 @@
+use alloc::string::String;
+use core::fmt::Write;
+
+use super::console::FONT;
+use super::tiled::{
+    MapLoan, RegularBackgroundSize, RegularMap, TileFormat, TileSet, TileSetting, Tiled0,
+    VRamManager, TRANSPARENT_TILE_INDEX,
+};
+use super::Priority;
+use crate::interrupt::VBlank;
+use crate::profile::CYCLES_PER_FRAME;
+use crate::timer::{Divider, Timer};
+
+/// The timer counts once every 256 cycles, so it overflows about once a second.
+const CYCLES_PER_TICK: u32 = 256;
+const CYCLES_PER_SECOND: u32 = 1 << 24;
+
+/// The number of characters which fit on a line of the screen
+const COLUMNS: usize = 30;
+
+/// Shares the last background palette with the debug console, which uses the
+/// same font.
+const PALETTE: u8 = 15;
+
+/// Shows the frames per second, how much of each frame is spent before waiting
+/// for vblank, and the number of frames dropped in the top left of the screen.
+/// This works on hardware as well as in emulators, so slowdowns can be spotted
+/// while playtesting on a real cartridge.
+///
+/// The numbers are updated once a second. The timer overflows after about a
+/// second, so a single frame which takes longer than that won't be measured
+/// correctly.
+///
+/// The overlay uses the last background palette and takes up one of the
+/// regular backgrounds for as long as it exists.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// let (gfx, mut vram) = gba.display.video.tiled0();
+/// let timers = gba.timers.timers();
+/// let mut overlay = PerformanceOverlay::new(&gfx, &mut vram, timers.timer2);
+///
+/// loop {
+///     input.update();
+///     if input.is_just_pressed(Button::SELECT) {
+///         overlay.toggle();
+///     }
+///
+///     update_game();
+///
+///     overlay.wait_for_vblank(&vblank);
+///     overlay.commit(&mut vram);
+/// }
+/// ```
+pub struct PerformanceOverlay<'a> {
+    map: MapLoan<'a, RegularMap>,
+    timer: Timer,
+    text: String,
+    text_changed: bool,
+    visible: bool,
+
+    frame_start: u16,
+    dropped_frames: u32,
+
+    // measured since the numbers were last updated
+    frames: u32,
+    elapsed_cycles: u32,
+    busy_cycles: u32,
+}
+
+impl<'a> PerformanceOverlay<'a> {
+    /// Reserves a background for the overlay and shows it above the other
+    /// backgrounds, using the timer to measure how long each frame takes.
+    pub fn new(gfx: &'a Tiled0, vram: &mut VRamManager, mut timer: Timer) -> Self {
+        let mut map = gfx.background(Priority::P0, RegularBackgroundSize::Background32x32);
+
+        vram.set_background_palette(PALETTE, &FONT.palettes[0]);
+        map.show();
+
+        timer.set_enabled(false);
+        timer.set_cascade(false);
+        timer.set_divider(Divider::Divider256);
+        timer.set_overflow_amount(0);
+        timer.set_enabled(true);
+
+        Self {
+            map,
+            frame_start: timer.value(),
+            timer,
+            text: String::new(),
+            text_changed: true,
+            visible: true,
+
+            dropped_frames: 0,
+
+            frames: 0,
+            elapsed_cycles: 0,
+            busy_cycles: 0,
+        }
+    }
+
+    /// Waits for vblank, recording how long the frame took up until now. Use
+    /// this instead of calling [`VBlank::wait_for_vblank`] directly.
+    pub fn wait_for_vblank(&mut self, vblank: &VBlank) {
+        let before_wait = self.timer.value();
+        vblank.wait_for_vblank();
+        let frame_end = self.timer.value();
+
+        self.record_frame(
+            before_wait.wrapping_sub(self.frame_start) as u32 * CYCLES_PER_TICK,
+            frame_end.wrapping_sub(self.frame_start) as u32 * CYCLES_PER_TICK,
+        );
+        self.frame_start = frame_end;
+    }
+
+    fn record_frame(&mut self, busy_cycles: u32, elapsed_cycles: u32) {
+        // any vblanks other than the one which was waited for were missed
+        let vblanks = ((elapsed_cycles + CYCLES_PER_FRAME / 2) / CYCLES_PER_FRAME).max(1);
+        self.dropped_frames += vblanks - 1;
+
+        self.frames += 1;
+        self.busy_cycles += busy_cycles;
+        self.elapsed_cycles += elapsed_cycles;
+
+        if self.elapsed_cycles >= CYCLES_PER_SECOND {
+            self.update_text();
+
+            self.frames = 0;
+            self.busy_cycles = 0;
+            self.elapsed_cycles = 0;
+        }
+    }
+
+    fn update_text(&mut self) {
+        let tenths_of_frames_per_second =
+            self.frames as u64 * CYCLES_PER_SECOND as u64 * 10 / self.elapsed_cycles as u64;
+        let load = self.busy_cycles as u64 * 100 / (self.frames as u64 * CYCLES_PER_FRAME as u64);
+
+        self.text.clear();
+        let _ = write!(
+            self.text,
+            "{}.{} FPS {}% CPU {} DROP",
+            tenths_of_frames_per_second / 10,
+            tenths_of_frames_per_second % 10,
+            load,
+            self.dropped_frames
+        );
+        self.text.truncate(COLUMNS);
+        self.text_changed = true;
+    }
+
+    /// The number of vblanks which were missed since the overlay was created,
+    /// because the frame before them took too long.
+    pub fn dropped_frames(&self) -> u32 {
+        self.dropped_frames
+    }
+
+    pub fn show(&mut self) {
+        self.visible = true;
+        self.map.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.map.hide();
+    }
+
+    /// Shows the overlay if it is hidden, and hides it if it is shown.
+    pub fn toggle(&mut self) {
+        if self.visible {
+            self.hide();
+        } else {
+            self.show();
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Updates the background with the latest numbers. This should be called
+    /// during vblank.
+    pub fn commit(&mut self, vram: &mut VRamManager) {
+        if self.text_changed {
+            let tiles = TileSet::new(FONT.tiles, TileFormat::FourBpp);
+
+            for column in 0..COLUMNS {
+                let tile_id = match self.text.as_bytes().get(column) {
+                    None | Some(b' ') => TRANSPARENT_TILE_INDEX,
+                    Some(&character) => (character - b' ') as u16,
+                };
+
+                self.map.set_tile(
+                    vram,
+                    (column as u16, 0).into(),
+                    &tiles,
+                    TileSetting::new(tile_id, false, false, PALETTE),
+                );
+            }
+
+            self.text_changed = false;
+        }
+
+        self.map.commit(vram);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn performance_overlay_reports_every_second(gba: &mut crate::Gba) {
+        let (gfx, mut vram) = gba.display.video.tiled0();
+        let timers = gba.timers.timers();
+        let mut overlay = PerformanceOverlay::new(&gfx, &mut vram, timers.timer2);
+
+        let seconds_of_frames = CYCLES_PER_SECOND.div_ceil(CYCLES_PER_FRAME);
+        for _ in 0..seconds_of_frames - 1 {
+            overlay.record_frame(CYCLES_PER_FRAME / 2, CYCLES_PER_FRAME);
+        }
+        assert!(overlay.text.is_empty());
+
+        overlay.record_frame(CYCLES_PER_FRAME / 2, CYCLES_PER_FRAME);
+        assert_eq!(overlay.text, "59.7 FPS 50% CPU 0 DROP");
+
+        // a frame taking two and a half frames means two vblanks were missed
+        overlay.record_frame(CYCLES_PER_FRAME * 5 / 2, CYCLES_PER_FRAME * 5 / 2);
+        assert_eq!(overlay.dropped_frames(), 2);
+
+        overlay.commit(&mut vram);
+        assert!(!overlay.text_changed);
+    }
+
+    #[test_case]
+    fn performance_overlay_measures_frames(gba: &mut crate::Gba) {
+        let (gfx, mut vram) = gba.display.video.tiled0();
+        let timers = gba.timers.timers();
+        let vblank = VBlank::get();
+
+        let mut overlay = PerformanceOverlay::new(&gfx, &mut vram, timers.timer2);
+        vblank.wait_for_vblank();
+        overlay.frame_start = overlay.timer.value();
+
+        for _ in 0..3 {
+            overlay.wait_for_vblank(&vblank);
+            overlay.commit(&mut vram);
+        }
+
+        assert_eq!(overlay.frames, 3);
+        assert_eq!(overlay.dropped_frames(), 0);
+        assert!(overlay.busy_cycles < overlay.elapsed_cycles);
+
+        overlay.toggle();
+        assert!(!overlay.is_visible());
+    }
+}
@@ -5,6 +5,7 @@ mod vram_manager;
 
 use agb_fixnum::Vector2D;
 pub use infinite_scrolled_map::{InfiniteScrolledMap, PartialUpdateStatus};
+pub(crate) use map::TRANSPARENT_TILE_INDEX;
 pub use map::{MapLoan, RegularMap};
 pub use tiled0::Tiled0;
 pub use vram_manager::{DynamicTile, TileFormat, TileIndex, TileSet, VRamManager};
@@ -347,7 +347,8 @@ impl VRamManager {
         }
     }
 
-    fn set_background_palette(&mut self, pal_index: u8, palette: &palette16::Palette16) {
+    /// Copies a palette to the given background palette slot without any checks.
+    pub(crate) fn set_background_palette(&mut self, pal_index: u8, palette: &palette16::Palette16) {
         for (colour_index, &colour) in palette.colours.iter().enumerate() {
             PALETTE_BACKGROUND.set(colour_index + 16 * pal_index as usize, colour);
         }
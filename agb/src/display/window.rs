@@ -0,0 +1,91 @@
+use bitflags::bitflags;
+
+use super::DISPLAY_CONTROL;
+use crate::memory_mapped::MemoryMapped;
+
+const WINDOW_OUTSIDE: MemoryMapped<u16> = unsafe { MemoryMapped::new(0x0400_004A) };
+
+bitflags! {
+    /// The layers which are shown within a window region.
+    pub struct WindowLayers: u16 {
+        const BG0 = 1 << 0;
+        const BG1 = 1 << 1;
+        const BG2 = 1 << 2;
+        const BG3 = 1 << 3;
+        const OBJECT = 1 << 4;
+        /// Whether colour special effects are applied, see [`Blend`][super::blend::Blend].
+        const BLEND = 1 << 5;
+    }
+}
+
+/// Controls the object window. Objects with the graphics mode
+/// [`GraphicsMode::ObjectWindow`] aren't displayed, and instead the pixels they
+/// cover make up the object window. This allows masks of any shape, for example
+/// a spotlight which only shows a background within it.
+///
+/// [`GraphicsMode::ObjectWindow`]: super::object::GraphicsMode::ObjectWindow
+///
+/// Changing the video mode resets whether the object window is enabled, so this
+/// should be done after getting a background.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// gba.display
+///     .window
+///     .set_object_window_enabled(true)
+///     .set_object_window_layers(WindowLayers::BG0 | WindowLayers::OBJECT)
+///     .set_outside_layers(WindowLayers::OBJECT);
+/// ```
+#[non_exhaustive]
+pub struct Window {}
+
+impl Window {
+    pub(crate) const unsafe fn new() -> Self {
+        Window {}
+    }
+
+    pub fn set_object_window_enabled(&mut self, enabled: bool) -> &mut Self {
+        DISPLAY_CONTROL.set_bits(enabled as u16, 1, 0xF);
+        self
+    }
+
+    /// Sets the layers which are shown outside of every window.
+    pub fn set_outside_layers(&mut self, layers: WindowLayers) -> &mut Self {
+        WINDOW_OUTSIDE.set_bits(layers.bits(), 6, 0);
+        self
+    }
+
+    /// Sets the layers which are shown inside the object window.
+    pub fn set_object_window_layers(&mut self, layers: WindowLayers) -> &mut Self {
+        WINDOW_OUTSIDE.set_bits(layers.bits(), 6, 8);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::GraphicsSettings;
+
+    #[test_case]
+    fn window_sets_registers(gba: &mut crate::Gba) {
+        gba.display
+            .window
+            .set_object_window_enabled(true)
+            .set_outside_layers(WindowLayers::OBJECT)
+            .set_object_window_layers(WindowLayers::BG0 | WindowLayers::BLEND);
+
+        assert_eq!(WINDOW_OUTSIDE.get(), 0b0010_0001_0001_0000);
+        assert_ne!(
+            DISPLAY_CONTROL.get() & GraphicsSettings::WINDOW_OBJECT.bits(),
+            0
+        );
+
+        gba.display.window.set_object_window_enabled(false);
+        assert_eq!(
+            DISPLAY_CONTROL.get() & GraphicsSettings::WINDOW_OBJECT.bits(),
+            0
+        );
+    }
+}
@@ -52,6 +52,9 @@ impl Interrupt {
             Interrupt::HBlank => {
                 DISPLAY_STATUS.set_bits(1, 1, 4);
             }
+            Interrupt::VCounter => {
+                DISPLAY_STATUS.set_bits(1, 1, 5);
+            }
             _ => {}
         }
     }
@@ -64,6 +67,9 @@ impl Interrupt {
             Interrupt::HBlank => {
                 DISPLAY_STATUS.set_bits(0, 1, 4);
             }
+            Interrupt::VCounter => {
+                DISPLAY_STATUS.set_bits(0, 1, 5);
+            }
             _ => {}
         }
     }
@@ -90,7 +96,7 @@ fn temporary_interrupt_disable() -> Disable {
     d
 }
 
-fn disable_interrupts() {
+pub(crate) fn disable_interrupts() {
     INTERRUPTS_ENABLED.set(0);
 }
 
@@ -361,6 +367,20 @@ mod tests {
     }
 }
 
+/// The addresses of the assembly interrupt handler, which calls the rust
+/// interrupt handler.
+fn assembly_interrupt_handler() -> core::ops::Range<usize> {
+    extern "C" {
+        static InterruptHandler: u8;
+        static agb_rs__interrupt_handler_end: u8;
+    }
+
+    unsafe {
+        (&InterruptHandler as *const u8 as usize)
+            ..(&agb_rs__interrupt_handler_end as *const u8 as usize)
+    }
+}
+
 #[must_use]
 /// The behaviour of this function is undefined in the sense that it will output
 /// some information in some way that can be interpreted in a way to give some
@@ -368,14 +388,43 @@ mod tests {
 /// interpret it are all subject to change at any time.
 ///
 /// With that out of the way, the current version will, in mgba, output the
-/// program counter at regular intervals. This can be used to see hot functions
-/// using, for example, addr2line.
+/// program counter and the return addresses on the stack at regular intervals.
+/// Running the game with `mgba-test-runner game.elf --profile <frames>` collects
+/// these samples for the given number of frames and then prints the hot
+/// functions, both by the time spent in the function itself and by the time
+/// spent in the function and everything it calls.
 pub fn profiler(timer: &mut crate::timer::Timer, period: u16) -> InterruptHandler {
     timer.set_interrupt(true);
     timer.set_overflow_amount(period);
     timer.set_enabled(true);
 
     add_interrupt_handler(timer.interrupt(), |_key: &CriticalSection| {
-        crate::println!("{:#010x}", crate::program_counter_before_interrupt());
+        use core::fmt::Write;
+
+        let mut mgba = match crate::mgba::Mgba::new() {
+            Some(mgba) => mgba,
+            None => return,
+        };
+
+        let _ = write!(
+            mgba,
+            "Profile: {:#010x}",
+            crate::program_counter_before_interrupt()
+        );
+
+        // The frames up to the assembly interrupt handler are the interrupt
+        // handlers themselves. After that are the functions which were running
+        // when the interrupt happened.
+        let assembly_handler = assembly_interrupt_handler();
+        let mut in_interrupted_code = false;
+        crate::backtrace::walk(|address| {
+            if in_interrupted_code {
+                let _ = write!(mgba, " {:#010x}", address);
+            } else if assembly_handler.contains(&address) {
+                in_interrupted_code = true;
+            }
+        });
+
+        mgba.set_level(crate::mgba::DebugLevel::Info);
     })
 }
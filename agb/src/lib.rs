@@ -114,15 +114,80 @@ pub use agb_image_converter::include_gfx;
 #[doc(hidden)]
 pub use agb_image_converter::include_aseprite_inner;
 
+#[doc(hidden)]
+pub use agb_image_converter::include_sprite_sheet_inner;
+
 #[doc(hidden)]
 pub use agb_image_converter::include_font as include_font_inner;
 
+/// Includes a ttf font rendered at the given size. By default only printable
+/// ASCII characters are included, and more can be added by passing a list of
+/// character sets separated by `+`. The available character sets are `ascii`,
+/// `latin1`, `latin-extended` and `kana`.
+///
+/// Starting with `anti_aliased;` keeps the levels of coverage at the edges of
+/// the letters for [`TextRenderer::with_anti_aliasing`], which doubles the size
+/// of the font in the ROM.
+///
+/// ```rust,ignore
+/// const FONT: Font = agb::include_font!("examples/font/yoster.ttf", 12, "latin1");
+/// const SMOOTH_FONT: Font = agb::include_font!(anti_aliased; "examples/font/yoster.ttf", 14);
+/// ```
+///
+/// [`TextRenderer::with_anti_aliasing`]: crate::display::TextRenderer::with_anti_aliasing
 #[macro_export]
 macro_rules! include_font {
+    (anti_aliased; $font_path: literal, $font_size: literal) => {{
+        use $crate::display;
+        $crate::include_font_inner!(anti_aliased; $font_path, $font_size)
+    }};
+    (anti_aliased; $font_path: literal, $font_size: literal, $character_sets: literal) => {{
+        use $crate::display;
+        $crate::include_font_inner!(anti_aliased; $font_path, $font_size, $character_sets)
+    }};
     ($font_path: literal, $font_size: literal) => {{
         use $crate::display;
         $crate::include_font_inner!($font_path, $font_size)
     }};
+    ($font_path: literal, $font_size: literal, $character_sets: literal) => {{
+        use $crate::display;
+        $crate::include_font_inner!($font_path, $font_size, $character_sets)
+    }};
+}
+
+#[doc(hidden)]
+pub use agb_image_converter::include_bitmap_font as include_bitmap_font_inner;
+
+/// Includes a font drawn as a grid of glyphs in a png, producing the same
+/// [`Font`][crate::display::Font] as [`include_font!`]. The glyphs go left to
+/// right then top to bottom in the order given by `characters`. Each glyph ends
+/// at the first pixel of the marker colour in its cell, or fills the whole cell
+/// if there isn't one, and fully transparent pixels or those of the transparent
+/// colour are left empty.
+///
+/// ```toml
+/// version = "1.0"
+///
+/// filename = "pixel_font.png"
+/// glyph_width = 8
+/// glyph_height = 10
+/// characters = "ABCDEFGHIJKLMNOPQRSTUVWXYZ"
+/// transparent_colour = "ffffff" # optional
+/// marker_colour = "ff0000" # optional
+/// baseline = 8 # rows above the baseline, defaults to glyph_height
+/// line_height = 11 # defaults to glyph_height + 1
+/// letter_spacing = 1 # pixels between letters, defaults to 1
+/// ```
+///
+/// ```rust,ignore
+/// const FONT: Font = agb::include_bitmap_font!("gfx/pixel_font.toml");
+/// ```
+#[macro_export]
+macro_rules! include_bitmap_font {
+    ($config_path: literal) => {{
+        use $crate::display;
+        $crate::include_bitmap_font_inner!($config_path)
+    }};
 }
 
 /// This macro declares the entry point to your game written using `agb`.
@@ -148,8 +213,11 @@ pub use agb_sound_converter::include_wav;
 
 extern crate alloc;
 mod agb_alloc;
+mod backtrace;
 
 mod bitarray;
+/// Keeping the panic message and recent log lines in SRAM to see after a crash.
+pub mod crash_log;
 /// Implements everything relating to things that are displayed on screen.
 pub mod display;
 mod dma;
@@ -157,15 +225,22 @@ mod dma;
 pub mod input;
 /// Interacting with the GBA interrupts
 pub mod interrupt;
+/// Logging at different levels to mgba, the on screen console or SRAM.
+pub mod logger;
 mod memory_mapped;
 /// Implements logging to the mgba emulator.
 pub mod mgba;
+mod panic_screen;
+/// Timing how many cycles parts of a game take.
+pub mod profile;
 /// Implementation of fixnums for working with non-integer values.
 pub use agb_fixnum as fixnum;
 /// Contains an implementation of a hashmap which suits the gameboy advance's hardware.
 pub mod hash_map;
 /// Simple random number generator
 pub mod rng;
+/// Reading and writing the cartridge's save media.
+pub mod save;
 mod single;
 /// Implements sound output.
 pub mod sound;
@@ -179,9 +254,14 @@ pub mod timer;
 #[allow(unused_must_use)]
 fn panic_implementation(info: &core::panic::PanicInfo) -> ! {
     use core::fmt::Write;
+    crash_log::save(format_args!("{}", info));
+
     if let Some(mut mgba) = mgba::Mgba::new() {
+        backtrace::log(&mut mgba);
         write!(mgba, "{}", info);
         mgba.set_level(mgba::DebugLevel::Fatal);
+    } else {
+        panic_screen::show(format_args!("{}", info));
     }
 
     #[allow(clippy::empty_loop)]
@@ -221,6 +301,8 @@ pub struct Gba {
     pub mixer: sound::mixer::MixerController,
     /// Manages access to the Game Boy Advance's 4 timers.
     pub timers: timer::TimerController,
+    /// Manages access to the cartridge's save media.
+    pub save: save::SaveManager,
 }
 
 impl Gba {
@@ -235,6 +317,7 @@ impl Gba {
             sound: sound::dmg::Sound::new(),
             mixer: sound::mixer::MixerController::new(),
             timers: timer::TimerController::new(),
+            save: save::SaveManager::new(),
         }
     }
 }
@@ -279,6 +362,7 @@ mod test_runner {
         if let Some(mut mgba) = mgba::Mgba::new() {
             mgba.print(format_args!("[failed]"), mgba::DebugLevel::Error)
                 .unwrap();
+            backtrace::log(&mut mgba);
             mgba.print(format_args!("Error: {}", info), mgba::DebugLevel::Fatal)
                 .unwrap();
         }
@@ -329,6 +413,24 @@ mod test_runner {
         .unwrap();
         display::busy_wait_for_vblank();
     }
+
+    /// Asks the mgba test runner to reset the console, which runs the tests
+    /// again from the start. Every test before the one which called this runs
+    /// again, so their output appears twice. The runner fails the run if a
+    /// reset is requested a second time, so the test must not call this again
+    /// after the reset.
+    pub fn reset() -> ! {
+        let mut mgba = crate::mgba::Mgba::new().unwrap();
+        mgba.print(
+            format_args!("Reset requested"),
+            crate::mgba::DebugLevel::Info,
+        )
+        .unwrap();
+
+        loop {
+            display::busy_wait_for_vblank();
+        }
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,307 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use bare_metal::Mutex;
+use bitflags::bitflags;
+use core::cell::Cell;
+use core::fmt::Write;
+
+use crate::memory_mapped::MemoryMapped1DArray;
+use crate::mgba::{DebugLevel, Mgba};
+
+pub use log::{Level, STATIC_MAX_LEVEL};
+
+bitflags! {
+    /// Where messages logged with [`error!`][crate::error],
+    /// [`warn!`][crate::warn], [`info!`][crate::info],
+    /// [`debug!`][crate::debug] and the `log` crate are sent.
+    pub struct Targets: u8 {
+        /// mgba's log, if running in mgba.
+        const MGBA = 1 << 0;
+        /// A [`DebugConsole`][crate::display::console::DebugConsole] which is
+        /// capturing [`println!`][crate::println].
+        const CONSOLE = 1 << 1;
+        /// A ring buffer of the most recent messages in the last 4KiB of SRAM,
+        /// which survives the console being turned off. This also declares
+        /// that the game uses SRAM, so that it is saved by emulators and flash
+        /// cartridges, and [`SaveManager`][crate::save::SaveManager] won't use
+        /// that part of SRAM for save data while this is enabled.
+        const SRAM = 1 << 2;
+    }
+}
+
+static TARGETS: Mutex<Cell<Targets>> = Mutex::new(Cell::new(Targets::MGBA.union(Targets::CONSOLE)));
+
+/// Chooses where logged messages are sent. By default they go to mgba and the
+/// on screen console.
+pub fn set_targets(targets: Targets) {
+    if targets.contains(Targets::SRAM) {
+        crate::save::use_sram();
+    }
+
+    crate::interrupt::free(|key| TARGETS.borrow(*key).set(targets));
+}
+
+pub fn targets() -> Targets {
+    crate::interrupt::free(|key| TARGETS.borrow(*key).get())
+}
+
+fn debug_level(level: Level) -> DebugLevel {
+    match level {
+        Level::Error => DebugLevel::Error,
+        Level::Warn => DebugLevel::Warning,
+        Level::Info => DebugLevel::Info,
+        Level::Debug | Level::Trace => DebugLevel::Debug,
+    }
+}
+
+/// Sends a message to each of the [`Targets`]. Use the logging macros rather
+/// than calling this directly so that messages below the maximum level are
+/// removed at compile time.
+#[doc(hidden)]
+pub fn write(level: Level, output: core::fmt::Arguments) {
+    let targets = targets();
+
+    if targets.contains(Targets::MGBA) {
+        if let Some(mut mgba) = Mgba::new() {
+            let _ = mgba.print(output, debug_level(level));
+        }
+    }
+
+    if targets.contains(Targets::CONSOLE) {
+        crate::display::console::print(format_args!("{} {}", level, output));
+    }
+
+    if targets.contains(Targets::SRAM) {
+        crate::interrupt::free(|_| {
+            let _ = writeln!(SramLog::open(), "{} {}", level, output);
+        });
+    }
+
+    crate::crash_log::remember(level, output);
+}
+
+struct Logger;
+
+impl log::Log for Logger {
+    fn enabled(&self, _metadata: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        write(record.level(), *record.args());
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: Logger = Logger;
+
+/// Sends messages logged with the `log` crate, for example by other `no_std`
+/// crates, to the same [`Targets`] as agb's logging macros. The maximum level
+/// can be set at compile time using the `log` crate's `max_level_*` and
+/// `release_max_level_*` features, which also apply to agb's macros. Calling
+/// this more than once does nothing.
+pub fn init() {
+    crate::interrupt::free(|_| unsafe {
+        if log::set_logger_racy(&LOGGER).is_ok() {
+            log::set_max_level_racy(log::LevelFilter::Trace);
+        }
+    });
+}
+
+/// Where the log starts in SRAM, which is the last 4KiB of the 32KiB.
+pub(crate) const SRAM_LOG_OFFSET: usize = 0x7000;
+const SRAM_LOG: MemoryMapped1DArray<u8, 0x1000> =
+    unsafe { MemoryMapped1DArray::new(0x0E00_0000 + SRAM_LOG_OFFSET) };
+
+const SRAM_LOG_MAGIC: [u8; 4] = *b"agbL";
+/// The magic followed by the little endian position of the next byte to write
+const HEADER_LENGTH: usize = 6;
+const BUFFER_LENGTH: usize = 0x1000 - HEADER_LENGTH;
+
+/// The ring buffer of messages in SRAM. Unused bytes are zero, and the oldest
+/// message is overwritten once the buffer is full.
+struct SramLog {
+    position: usize,
+}
+
+impl SramLog {
+    fn existing() -> Option<Self> {
+        let has_magic = SRAM_LOG_MAGIC
+            .iter()
+            .enumerate()
+            .all(|(i, &byte)| SRAM_LOG.get(i) == byte);
+
+        let position = SRAM_LOG.get(4) as usize | ((SRAM_LOG.get(5) as usize) << 8);
+
+        if has_magic && position < BUFFER_LENGTH {
+            Some(Self { position })
+        } else {
+            None
+        }
+    }
+
+    fn open() -> Self {
+        Self::existing().unwrap_or_else(|| {
+            for i in 0..BUFFER_LENGTH {
+                SRAM_LOG.set(HEADER_LENGTH + i, 0);
+            }
+            for (i, &byte) in SRAM_LOG_MAGIC.iter().enumerate() {
+                SRAM_LOG.set(i, byte);
+            }
+
+            let mut log = Self { position: 0 };
+            log.save_position();
+            log
+        })
+    }
+
+    fn save_position(&mut self) {
+        SRAM_LOG.set(4, self.position as u8);
+        SRAM_LOG.set(5, (self.position >> 8) as u8);
+    }
+
+    fn contents(&self) -> Vec<u8> {
+        let is_full = SRAM_LOG.get(HEADER_LENGTH + self.position) != 0;
+
+        let mut contents: Vec<u8> = (self.position..BUFFER_LENGTH)
+            .chain(0..self.position)
+            .map(|i| SRAM_LOG.get(HEADER_LENGTH + i))
+            .filter(|&byte| byte != 0)
+            .collect();
+
+        // the oldest message has been partly overwritten
+        if is_full {
+            let first_line_end = contents.iter().position(|&byte| byte == b'\n');
+            contents.drain(..first_line_end.map_or(contents.len(), |end| end + 1));
+        }
+
+        contents
+    }
+}
+
+impl Write for SramLog {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        for byte in s.bytes().filter(|&byte| byte != 0) {
+            SRAM_LOG.set(HEADER_LENGTH + self.position, byte);
+            self.position = (self.position + 1) % BUFFER_LENGTH;
+        }
+
+        self.save_position();
+        Ok(())
+    }
+}
+
+/// The messages logged to SRAM, oldest first, including those from before the
+/// console was last turned off. Returns `None` if nothing has been logged to
+/// SRAM.
+pub fn sram_log() -> Option<String> {
+    crate::interrupt::free(|_| {
+        SramLog::existing().map(|log| String::from_utf8_lossy(&log.contents()).into_owned())
+    })
+}
+
+/// Removes all the messages logged to SRAM.
+pub fn clear_sram_log() {
+    crate::interrupt::free(|_| SRAM_LOG.set(0, 0));
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log {
+    ($level:expr, $($arg:tt)+) => {
+        {
+            let level = $level;
+            if level <= $crate::logger::STATIC_MAX_LEVEL {
+                $crate::logger::write(level, format_args!($($arg)+));
+            }
+        }
+    };
+}
+
+/// Logs a message at the error level to the [`Targets`][crate::logger::Targets]
+/// which are enabled.
+#[macro_export]
+macro_rules! error {
+    ($($arg:tt)+) => {
+        $crate::__log!($crate::logger::Level::Error, $($arg)+)
+    };
+}
+
+/// Logs a message at the warning level to the
+/// [`Targets`][crate::logger::Targets] which are enabled.
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)+) => {
+        $crate::__log!($crate::logger::Level::Warn, $($arg)+)
+    };
+}
+
+/// Logs a message at the info level to the [`Targets`][crate::logger::Targets]
+/// which are enabled.
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)+) => {
+        $crate::__log!($crate::logger::Level::Info, $($arg)+)
+    };
+}
+
+/// Logs a message at the debug level to the [`Targets`][crate::logger::Targets]
+/// which are enabled.
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)+) => {
+        $crate::__log!($crate::logger::Level::Debug, $($arg)+)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+
+    #[test_case]
+    fn sram_log_keeps_the_latest_messages(_gba: &mut crate::Gba) {
+        let previous_targets = targets();
+        set_targets(Targets::SRAM);
+        clear_sram_log();
+        assert!(sram_log().is_none());
+
+        let name = "world";
+        crate::info!("hello, {}", name);
+        crate::error!("something went wrong");
+        assert_eq!(
+            sram_log().unwrap(),
+            "INFO hello, world\nERROR something went wrong\n"
+        );
+
+        // once full, the oldest messages are dropped whole
+        for i in 0..BUFFER_LENGTH / 8 {
+            crate::debug!("{:06}", i);
+        }
+        let log = sram_log().unwrap();
+        let last = format!("DEBUG {:06}\n", BUFFER_LENGTH / 8 - 1);
+        assert!(log.ends_with(&last));
+        assert!(log.starts_with("DEBUG "));
+        assert!(log.len() <= BUFFER_LENGTH);
+
+        clear_sram_log();
+        set_targets(previous_targets);
+    }
+
+    #[test_case]
+    fn log_crate_messages_are_logged(_gba: &mut crate::Gba) {
+        let previous_targets = targets();
+        set_targets(Targets::SRAM);
+        clear_sram_log();
+
+        init();
+        init();
+        let number = 3;
+        log::warn!("from the log crate {}", number);
+        assert_eq!(sram_log().unwrap(), "WARN from the log crate 3\n");
+
+        clear_sram_log();
+        set_targets(previous_targets);
+    }
+}
@@ -79,12 +79,17 @@ impl core::fmt::Write for Mgba {
     }
 }
 
+/// Prints to mgba's log if running in mgba. Otherwise, the output is shown on a
+/// [`DebugConsole`][crate::display::console::DebugConsole] if one is capturing
+/// it, and is discarded if not.
 #[macro_export]
 macro_rules! println {
     ($( $x:expr ),*) => {
         {
             if let Some(mut mgba) = $crate::mgba::Mgba::new() {
                 let _ = mgba.print(format_args!($($x,)*), $crate::mgba::DebugLevel::Info);
+            } else {
+                $crate::display::console::print(format_args!($($x,)*));
             }
         }
     };
@@ -0,0 +1,183 @@
+use core::fmt::Write;
+
+use crate::display::bitmap3::Bitmap3;
+use crate::display::console::FONT;
+use crate::display::{HEIGHT, WIDTH};
+use crate::memory_mapped::MemoryMapped;
+
+const BACKGROUND_COLOUR: u16 = 0x2800;
+
+const CHARACTER_SIZE: i32 = 8;
+const MARGIN: i32 = 8;
+
+const BLEND_CONTROL: MemoryMapped<u16> = unsafe { MemoryMapped::new(0x0400_0050) };
+
+const BG2_AFFINE_MATRIX: [MemoryMapped<u16>; 4] = unsafe {
+    [
+        MemoryMapped::new(0x0400_0020),
+        MemoryMapped::new(0x0400_0022),
+        MemoryMapped::new(0x0400_0024),
+        MemoryMapped::new(0x0400_0026),
+    ]
+};
+const BG2_REFERENCE_POINT: [MemoryMapped<u32>; 2] = unsafe {
+    [
+        MemoryMapped::new(0x0400_0028),
+        MemoryMapped::new(0x0400_002C),
+    ]
+};
+
+/// Draws text onto the mode 3 bitmap using the debug console's font, wrapping
+/// at the edge of the screen. It doesn't allocate, so can be used even if the
+/// panic was caused by running out of memory.
+struct PanicWriter {
+    bitmap: Bitmap3,
+    x: i32,
+    y: i32,
+}
+
+impl PanicWriter {
+    fn new(bitmap: Bitmap3) -> Self {
+        Self {
+            bitmap,
+            x: MARGIN,
+            y: MARGIN,
+        }
+    }
+
+    fn clear(&mut self) {
+        for y in 0..HEIGHT {
+            for x in 0..WIDTH {
+                self.bitmap.draw_point(x, y, BACKGROUND_COLOUR);
+            }
+        }
+    }
+
+    fn new_line(&mut self) {
+        self.x = MARGIN;
+        self.y += CHARACTER_SIZE;
+    }
+
+    fn draw_char(&mut self, c: char) {
+        if c == '\n' {
+            self.new_line();
+            return;
+        }
+
+        if self.x + CHARACTER_SIZE > WIDTH - MARGIN {
+            self.new_line();
+        }
+
+        // anything which doesn't fit is lost, but the start of the message is
+        // the most useful part anyway
+        if self.y + CHARACTER_SIZE > HEIGHT {
+            return;
+        }
+
+        let character = match c {
+            ' '..='~' => c as u8,
+            _ => b'?',
+        };
+
+        let palette = &FONT.palettes[0];
+        let tile = &FONT.tiles[(character - b' ') as usize * 32..][..32];
+
+        for y in 0..CHARACTER_SIZE {
+            for x in 0..CHARACTER_SIZE {
+                let colour_index = (tile[(y * 4 + x / 2) as usize] >> ((x & 1) * 4)) & 0xf;
+                if colour_index != 0 {
+                    self.bitmap.draw_point(
+                        self.x + x,
+                        self.y + y,
+                        palette.colour(colour_index as usize),
+                    );
+                }
+            }
+        }
+
+        self.x += CHARACTER_SIZE;
+    }
+}
+
+impl Write for PanicWriter {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        for c in s.chars() {
+            self.draw_char(c);
+        }
+
+        Ok(())
+    }
+}
+
+/// Clears the mode 3 bitmap and draws the text onto it, resetting anything the
+/// game did to the display which would stop the bitmap from being seen as is.
+/// Interrupts are disabled while drawing so that interrupt handlers can't change
+/// the display part way through.
+pub(crate) fn draw(bitmap: Bitmap3, text: core::fmt::Arguments) {
+    crate::interrupt::free(|_| {
+        BLEND_CONTROL.set(0);
+        for (register, value) in BG2_AFFINE_MATRIX.iter().zip([1 << 8, 0, 0, 1 << 8]) {
+            register.set(value);
+        }
+        for register in BG2_REFERENCE_POINT.iter() {
+            register.set(0);
+        }
+
+        let mut writer = PanicWriter::new(bitmap);
+        writer.clear();
+
+        let _ = writer.write_fmt(text);
+    });
+}
+
+/// Switches to bitmap mode 3 and shows the panic message on the screen, so the
+/// cause of a crash can be seen when not running in mgba. Interrupts are left
+/// disabled so that nothing changes the display afterwards.
+pub(crate) fn show(message: core::fmt::Arguments) {
+    crate::interrupt::disable_interrupts();
+
+    draw(
+        unsafe { Bitmap3::new() },
+        format_args!("The game crashed!\n\n{}", message),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn panic_writer_wraps_text(gba: &mut crate::Gba) {
+        let mut writer = PanicWriter::new(gba.display.video.bitmap3());
+
+        let columns = (WIDTH - 2 * MARGIN) / CHARACTER_SIZE;
+        for _ in 0..columns {
+            writer.draw_char('a');
+        }
+        assert_eq!((writer.x, writer.y), (WIDTH - MARGIN, MARGIN));
+
+        writer.draw_char('b');
+        assert_eq!((writer.x, writer.y), (MARGIN + CHARACTER_SIZE, MARGIN + 8));
+
+        write!(writer, "\nc").unwrap();
+        assert_eq!((writer.x, writer.y), (MARGIN + CHARACTER_SIZE, MARGIN + 16));
+
+        // text past the bottom of the screen is dropped rather than panicking
+        for _ in 0..HEIGHT {
+            writer.draw_char('\n');
+        }
+        writer.draw_char('d');
+    }
+
+    #[test_case]
+    fn panic_screen_clears_the_screen(gba: &mut crate::Gba) {
+        let reason = "testing";
+        // interrupts are turned back on afterwards so that the other tests
+        // still work
+        crate::interrupt::free(|_| show(format_args!("panicked because {}", reason)));
+
+        let bitmap = gba.display.video.bitmap3();
+        assert_eq!(bitmap.read_point(0, 0), BACKGROUND_COLOUR);
+        assert_eq!(bitmap.read_point(WIDTH - 1, HEIGHT - 1), BACKGROUND_COLOUR);
+    }
+}
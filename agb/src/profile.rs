@@ -0,0 +1,306 @@
+use alloc::vec::Vec;
+use bare_metal::Mutex;
+use core::cell::RefCell;
+
+use crate::interrupt::free;
+use crate::mgba::{self, DebugLevel, Mgba};
+use crate::timer::{Divider, Timer};
+
+/// The number of cycles between the start of one frame and the next.
+pub const CYCLES_PER_FRAME: u32 = 280_896;
+
+/// Tags written to mgba's cycle register start from here so they don't clash
+/// with the tag used by the test runner. Each scope uses two tags, one for
+/// when it starts and one for when it ends.
+const FIRST_SCOPE_TAG: u16 = 0x1000;
+
+/// Two timers cascaded together to count cycles in 32 bits.
+struct CycleCounter {
+    low: Timer,
+    high: Timer,
+}
+
+impl CycleCounter {
+    fn cycles(&self) -> u32 {
+        // read the high part either side of the low part, in case the low part
+        // overflowed between the reads
+        loop {
+            let high = self.high.value();
+            let low = self.low.value();
+            if self.high.value() == high {
+                return ((high as u32) << 16) | low as u32;
+            }
+        }
+    }
+}
+
+/// The cycles spent in a scope over all the times it was run.
+#[derive(Clone, Debug)]
+pub struct ScopeStats {
+    name: &'static str,
+    count: u32,
+    total_cycles: u64,
+    min_cycles: u32,
+    max_cycles: u32,
+}
+
+impl ScopeStats {
+    fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            count: 0,
+            total_cycles: 0,
+            min_cycles: u32::MAX,
+            max_cycles: 0,
+        }
+    }
+
+    fn record(&mut self, cycles: u32) {
+        self.count += 1;
+        self.total_cycles += cycles as u64;
+        self.min_cycles = self.min_cycles.min(cycles);
+        self.max_cycles = self.max_cycles.max(cycles);
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The number of times the scope has finished.
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn min_cycles(&self) -> u32 {
+        if self.count == 0 {
+            0
+        } else {
+            self.min_cycles
+        }
+    }
+
+    pub fn max_cycles(&self) -> u32 {
+        self.max_cycles
+    }
+
+    pub fn average_cycles(&self) -> u32 {
+        if self.count == 0 {
+            0
+        } else {
+            (self.total_cycles / self.count as u64) as u32
+        }
+    }
+}
+
+struct Profiler {
+    counter: Option<CycleCounter>,
+    scopes: Vec<ScopeStats>,
+}
+
+static PROFILER: Mutex<RefCell<Profiler>> = Mutex::new(RefCell::new(Profiler {
+    counter: None,
+    scopes: Vec::new(),
+}));
+
+/// Starts timing scopes on the device using the given timers, which are
+/// cascaded together to count every cycle. Without this, scopes are only timed
+/// by mgba and reported by the test runner.
+pub fn enable(mut low: Timer, mut high: Timer) {
+    low.set_enabled(false);
+    high.set_enabled(false);
+
+    low.set_cascade(false);
+    low.set_divider(Divider::Divider1);
+    low.set_overflow_amount(0);
+
+    high.set_cascade(true);
+    high.set_overflow_amount(0);
+
+    high.set_enabled(true);
+    low.set_enabled(true);
+
+    free(|key| {
+        PROFILER.borrow(*key).borrow_mut().counter = Some(CycleCounter { low, high });
+    });
+}
+
+/// The statistics of every scope which has been entered since the last reset.
+pub fn stats() -> Vec<ScopeStats> {
+    free(|key| PROFILER.borrow(*key).borrow().scopes.clone())
+}
+
+/// Forgets the cycles recorded so far, for example to measure each level
+/// separately.
+pub fn reset() {
+    free(|key| {
+        for scope in PROFILER.borrow(*key).borrow_mut().scopes.iter_mut() {
+            *scope = ScopeStats::new(scope.name);
+        }
+    });
+}
+
+/// Prints the minimum, average and maximum cycles spent in each scope using
+/// [`println!`][crate::println], along with the average as a percentage of a
+/// frame.
+pub fn dump() {
+    for scope in stats() {
+        crate::println!(
+            "{}: {} times, min {}, avg {}, max {} cycles ({}.{:02}% of a frame)",
+            scope.name(),
+            scope.count(),
+            scope.min_cycles(),
+            scope.average_cycles(),
+            scope.max_cycles(),
+            scope.average_cycles() * 100 / CYCLES_PER_FRAME,
+            scope.average_cycles() * 10000 / CYCLES_PER_FRAME % 100
+        );
+    }
+}
+
+fn scope_tags(id: usize) -> (u16, u16) {
+    let start = FIRST_SCOPE_TAG + 2 * id as u16;
+    (start, start + 1)
+}
+
+/// Times the code from when it is created until it is dropped. Use
+/// [`scope!`] rather than creating this directly.
+#[must_use]
+pub struct Scope {
+    id: usize,
+    start: Option<u32>,
+}
+
+impl Scope {
+    pub fn new(name: &'static str) -> Self {
+        let (id, start) = free(|key| {
+            let mut profiler = PROFILER.borrow(*key).borrow_mut();
+
+            let id = match profiler.scopes.iter().position(|scope| scope.name == name) {
+                Some(id) => id,
+                None => {
+                    profiler.scopes.push(ScopeStats::new(name));
+                    let id = profiler.scopes.len() - 1;
+
+                    if let Some(mut mgba) = Mgba::new() {
+                        let _ = mgba.print(
+                            format_args!("Scope tag: {} {}", scope_tags(id).0, name),
+                            DebugLevel::Info,
+                        );
+                    }
+
+                    id
+                }
+            };
+
+            (id, profiler.counter.as_ref().map(CycleCounter::cycles))
+        });
+
+        if Mgba::new().is_some() {
+            mgba::number_of_cycles_tagged(scope_tags(id).0);
+        }
+
+        Self { id, start }
+    }
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        if Mgba::new().is_some() {
+            mgba::number_of_cycles_tagged(scope_tags(self.id).1);
+        }
+
+        free(|key| {
+            let mut profiler = PROFILER.borrow(*key).borrow_mut();
+
+            let end = profiler.counter.as_ref().map(CycleCounter::cycles);
+            if let (Some(start), Some(end)) = (self.start, end) {
+                profiler.scopes[self.id].record(end.wrapping_sub(start));
+            }
+        });
+    }
+}
+
+#[doc(inline)]
+pub use crate::profile_scope as scope;
+
+#[macro_export]
+#[doc(hidden)]
+/// Times the rest of the enclosing block, adding the cycles to the statistics
+/// for the given name which can be printed with [`dump`][crate::profile::dump].
+///
+/// In mgba, the start and end of each scope are also sent to the emulator's
+/// cycle register and the test runner prints the cycles spent in each scope
+/// when it finishes. On hardware and in other emulators, call [`enable`][crate::profile::enable] with
+/// two timers to record them on the device.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// agb::profile::enable(timers.timer2, timers.timer3);
+///
+/// loop {
+///     {
+///         agb::profile::scope!("physics");
+///         update_physics();
+///     }
+///
+///     {
+///         agb::profile::scope!("render");
+///         render();
+///     }
+///
+///     if input.is_just_pressed(Button::SELECT) {
+///         agb::profile::dump();
+///     }
+/// }
+/// ```
+macro_rules! profile_scope {
+    ($name:expr) => {
+        let _profile_scope = $crate::profile::Scope::new($name);
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(name: &str) -> ScopeStats {
+        stats()
+            .into_iter()
+            .find(|scope| scope.name() == name)
+            .unwrap()
+    }
+
+    #[test_case]
+    fn profile_scopes_record_cycles(gba: &mut crate::Gba) {
+        let timers = gba.timers.timers();
+        enable(timers.timer2, timers.timer3);
+
+        for i in 0..3 {
+            scope!("profile test outer");
+            for _ in 0..i {
+                scope!("profile test inner");
+                crate::display::busy_wait_for_vblank();
+            }
+        }
+
+        let outer = find("profile test outer");
+        let inner = find("profile test inner");
+
+        assert_eq!(outer.count(), 3);
+        assert_eq!(inner.count(), 3);
+        assert!(inner.min_cycles() > 0);
+        assert!(inner.max_cycles() <= 2 * CYCLES_PER_FRAME);
+        assert!(outer.max_cycles() >= 2 * inner.min_cycles());
+        assert!(outer.min_cycles() <= outer.average_cycles());
+
+        reset();
+        assert_eq!(find("profile test outer").count(), 0);
+
+        free(|key| {
+            let mut profiler = PROFILER.borrow(*key).borrow_mut();
+            profiler.counter = None;
+            profiler.scopes = Vec::new();
+        });
+    }
+}
@@ -0,0 +1,232 @@
+use crate::memory_mapped::{MemoryMapped, MemoryMapped1DArray};
+
+/// The size of the battery backed SRAM found on most cartridges which use it.
+pub const SRAM_SIZE: usize = 32 * 1024;
+
+const SRAM: MemoryMapped1DArray<u8, SRAM_SIZE> = unsafe { MemoryMapped1DArray::new(0x0E00_0000) };
+
+const WAITSTATE_CONTROL: MemoryMapped<u16> = unsafe { MemoryMapped::new(0x0400_0204) };
+/// The slowest SRAM access time of 8 cycles, which all cartridges support
+const SRAM_WAITSTATE_8_CYCLES: u16 = 0b11;
+
+#[repr(align(4))]
+struct SaveMarker([u8; 12]);
+
+/// Emulators and flash cartridges look for this string in the ROM to find out
+/// which kind of save media the game uses. It is only linked in if
+/// [`use_sram`] is called.
+static SRAM_MARKER: SaveMarker = SaveMarker(*b"SRAM_Vnnn\0\0\0");
+
+/// Declares that the game uses battery backed SRAM, so that emulators and
+/// flash cartridges save it, and sets the access time SRAM needs. This must be
+/// called by anything which writes to SRAM.
+pub(crate) fn use_sram() {
+    // make sure the marker is kept in the ROM
+    unsafe { core::ptr::read_volatile(&SRAM_MARKER.0[0]) };
+
+    WAITSTATE_CONTROL.set(WAITSTATE_CONTROL.get() | SRAM_WAITSTATE_8_CYCLES);
+}
+
+/// The reasons reading from or writing to the save media can fail.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// No save media has been initialised.
+    NoMedia,
+    /// The range being read or written goes past the end of the save media.
+    OutOfBounds,
+    /// The data read back after writing was different, which usually means the
+    /// cartridge doesn't have the save media which was initialised.
+    WriteFailed,
+}
+
+/// How much of the start of SRAM isn't being used by the logger or crash log.
+fn available_sram() -> usize {
+    if crate::crash_log::is_enabled() {
+        crate::crash_log::SRAM_CRASH_LOG_OFFSET
+    } else if crate::logger::targets().contains(crate::logger::Targets::SRAM) {
+        crate::logger::SRAM_LOG_OFFSET
+    } else {
+        SRAM_SIZE
+    }
+}
+
+/// SRAM is on an 8-bit bus, so it must only be read and written a byte at a
+/// time.
+struct Sram;
+
+impl Sram {
+    fn read(&self, offset: usize, buffer: &mut [u8]) {
+        for (i, byte) in buffer.iter_mut().enumerate() {
+            *byte = SRAM.get(offset + i);
+        }
+    }
+
+    fn write(&mut self, offset: usize, data: &[u8]) -> Result<(), Error> {
+        for (i, &byte) in data.iter().enumerate() {
+            SRAM.set(offset + i, byte);
+            if SRAM.get(offset + i) != byte {
+                return Err(Error::WriteFailed);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Gives access to the cartridge's save media, which keeps its contents while
+/// the console is turned off.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// gba.save.init_sram();
+///
+/// let mut high_score = [0; 4];
+/// gba.save.read(0, &mut high_score)?;
+///
+/// gba.save.write(0, &new_high_score.to_le_bytes())?;
+/// ```
+pub struct SaveManager {
+    sram: Option<Sram>,
+}
+
+impl SaveManager {
+    pub(crate) const fn new() -> Self {
+        Self { sram: None }
+    }
+
+    /// Declares that the game uses battery backed SRAM, so that emulators save
+    /// it, and sets up access to it.
+    ///
+    /// The [`logger`][crate::logger] uses the last 4KiB of SRAM while it is
+    /// writing to it, and the [`crash_log`][crate::crash_log] uses the 1KiB
+    /// before that while it is enabled, which can't be used for save data at
+    /// the same time.
+    pub fn init_sram(&mut self) {
+        use_sram();
+
+        self.sram = Some(Sram);
+    }
+
+    /// The size of the save media in bytes, or 0 if it hasn't been initialised.
+    /// This doesn't include the parts of SRAM used by the
+    /// [`logger`][crate::logger] and [`crash_log`][crate::crash_log] while
+    /// they are enabled.
+    pub fn len(&self) -> usize {
+        match self.sram {
+            Some(_) => available_sram(),
+            None => 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn check_bounds(&self, offset: usize, length: usize) -> Result<(), Error> {
+        if self.is_empty() {
+            return Err(Error::NoMedia);
+        }
+
+        match offset.checked_add(length) {
+            Some(end) if end <= self.len() => Ok(()),
+            _ => Err(Error::OutOfBounds),
+        }
+    }
+
+    /// Fills the buffer with the bytes of the save media starting at the offset.
+    pub fn read(&self, offset: usize, buffer: &mut [u8]) -> Result<(), Error> {
+        self.check_bounds(offset, buffer.len())?;
+
+        match &self.sram {
+            Some(sram) => {
+                sram.read(offset, buffer);
+                Ok(())
+            }
+            None => Err(Error::NoMedia),
+        }
+    }
+
+    /// Writes the data to the save media starting at the offset, checking that
+    /// each byte was written correctly.
+    pub fn write(&mut self, offset: usize, data: &[u8]) -> Result<(), Error> {
+        self.check_bounds(offset, data.len())?;
+
+        match &mut self.sram {
+            Some(sram) => sram.write(offset, data),
+            None => Err(Error::NoMedia),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PATTERN: [u8; 8] = *b"agb save";
+
+    #[test_case]
+    fn save_reads_back_what_was_written(gba: &mut crate::Gba) {
+        let mut buffer = [0; 4];
+        assert_eq!(SaveManager::new().read(0, &mut buffer), Err(Error::NoMedia));
+
+        gba.save.init_sram();
+
+        gba.save.write(0x100, &[1, 2, 3, 4]).unwrap();
+        gba.save.read(0x100, &mut buffer).unwrap();
+        assert_eq!(buffer, [1, 2, 3, 4]);
+
+        assert_eq!(
+            gba.save.write(SRAM_SIZE - 2, &[1, 2, 3]),
+            Err(Error::OutOfBounds)
+        );
+        assert_eq!(
+            gba.save.read(usize::MAX, &mut buffer),
+            Err(Error::OutOfBounds)
+        );
+
+        gba.save.write(0x100, &[0; 4]).unwrap();
+    }
+
+    #[test_case]
+    fn save_leaves_space_for_the_sram_logs(gba: &mut crate::Gba) {
+        use crate::logger::{self, Targets};
+
+        gba.save.init_sram();
+        assert_eq!(gba.save.len(), SRAM_SIZE);
+
+        let previous_targets = logger::targets();
+        logger::set_targets(Targets::SRAM);
+
+        assert_eq!(gba.save.len(), logger::SRAM_LOG_OFFSET);
+        assert_eq!(
+            gba.save.write(logger::SRAM_LOG_OFFSET, &[1]),
+            Err(Error::OutOfBounds)
+        );
+
+        crate::crash_log::enable();
+        assert_eq!(gba.save.len(), crate::crash_log::SRAM_CRASH_LOG_OFFSET);
+
+        crate::crash_log::disable();
+        logger::set_targets(previous_targets);
+        assert_eq!(gba.save.len(), SRAM_SIZE);
+    }
+
+    #[test_case]
+    fn save_persists_across_reset(gba: &mut crate::Gba) {
+        gba.save.init_sram();
+
+        let mut buffer = [0; PATTERN.len()];
+        gba.save.read(0, &mut buffer).unwrap();
+
+        // the second time round, after the reset, the pattern should still be
+        // there
+        if buffer == PATTERN {
+            gba.save.write(0, &[0; PATTERN.len()]).unwrap();
+            return;
+        }
+
+        gba.save.write(0, &PATTERN).unwrap();
+        crate::test_runner::reset();
+    }
+}
@@ -76,6 +76,8 @@ void free_runner(struct MGBA* mgba) {
 
 void advance_frame(struct MGBA* mgba) { mgba->core->runFrame(mgba->core); }
 
+void reset(struct MGBA* mgba) { mgba->core->reset(mgba->core); }
+
 struct video_buffer get_video_buffer(struct MGBA* mgba) {
     return mgba->videoBuffer;
 }
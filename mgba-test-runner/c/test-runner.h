@@ -20,4 +20,5 @@ struct MGBA* new_runner(char filename[]);
 void free_runner(struct MGBA* mgba);
 void set_logger(struct MGBA*, struct callback);
 void advance_frame(struct MGBA* mgba);
+void reset(struct MGBA* mgba);
 struct video_buffer get_video_buffer(struct MGBA* mgba);
\ No newline at end of file
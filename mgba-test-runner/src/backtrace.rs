@@ -0,0 +1,96 @@
+use addr2line::gimli::{EndianRcSlice, RunTimeEndian};
+use addr2line::object;
+use addr2line::Context;
+use anyhow::Error;
+
+/// Turns the addresses logged by a game, such as the return addresses in a
+/// backtrace, into function names, files and lines using the debug information
+/// in the ELF file being run.
+pub struct Symboliser {
+    context: Context<EndianRcSlice<RunTimeEndian>>,
+}
+
+impl Symboliser {
+    pub fn new(elf_path: &str) -> Result<Self, Error> {
+        let data = std::fs::read(elf_path)?;
+        let object = object::File::parse(&*data)?;
+        let context = Context::new(&object)?;
+
+        Ok(Self { context })
+    }
+
+    /// The functions at the given address, starting with the innermost one if
+    /// some have been inlined, along with their locations if known.
+    pub fn frames(&self, address: u32) -> Result<Vec<Frame>, Error> {
+        let mut frames = self.context.find_frames(address as u64).skip_all_loads()?;
+        let mut result = Vec::new();
+
+        while let Some(frame) = frames.next()? {
+            let function = match &frame.function {
+                Some(function) => function.demangle()?.into_owned(),
+                None => "<unknown>".to_owned(),
+            };
+
+            let location = frame.location.map(|location| {
+                format!(
+                    "{}:{}:{}",
+                    location.file.unwrap_or("<unknown>"),
+                    location.line.unwrap_or(0),
+                    location.column.unwrap_or(0)
+                )
+            });
+
+            result.push(Frame { function, location });
+        }
+
+        Ok(result)
+    }
+}
+
+pub struct Frame {
+    pub function: String,
+    pub location: Option<String>,
+}
+
+/// The return address is the instruction after the call, so this gives an
+/// address inside the call itself which is what the location should point to.
+pub fn call_site(return_address: u32) -> u32 {
+    if return_address & 1 == 1 {
+        (return_address & !1).saturating_sub(2)
+    } else {
+        return_address.saturating_sub(4)
+    }
+}
+
+/// Parses an address logged by agb, which is in hexadecimal with a leading `0x`.
+pub fn parse_address(address: &str) -> Option<u32> {
+    u32::from_str_radix(address.trim_start_matches("0x"), 16).ok()
+}
+
+/// Prints the backtrace from a message of space separated return addresses,
+/// without symbols if the ELF's debug information couldn't be read.
+pub fn print_backtrace(addresses: &str, symboliser: Option<&Symboliser>) {
+    println!("Backtrace:");
+
+    for (i, address) in addresses.split_whitespace().enumerate() {
+        println!("{:4}: {}", i, address);
+
+        let address = match parse_address(address) {
+            Some(address) => address,
+            None => continue,
+        };
+
+        match symboliser.map(|symboliser| symboliser.frames(call_site(address))) {
+            Some(Ok(frames)) if !frames.is_empty() => {
+                for frame in frames {
+                    println!("        {}", frame.function);
+                    if let Some(location) = frame.location {
+                        println!("            at {}", location);
+                    }
+                }
+            }
+            Some(Err(e)) => println!("        <failed to read debug info: {}>", e),
+            _ => println!("        <unknown>"),
+        }
+    }
+}
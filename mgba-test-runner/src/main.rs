@@ -1,5 +1,7 @@
 #![allow(clippy::all)]
 
+mod backtrace;
+mod profile;
 mod runner;
 use anyhow::{anyhow, Error};
 use image::io::Reader;
@@ -25,14 +27,20 @@ enum Timing {
 
 const TEST_RUNNER_TAG: u16 = 785;
 
-fn test_file(file_to_run: &str) -> Status {
+fn test_file(file_to_run: &str, profile_frames: Option<u32>) -> Status {
     let mut finished = Status::Running;
     let debug_reader_mutex = Regex::new(r"(?s)^\[(.*)\] GBA Debug: (.*)$").unwrap();
     let tagged_cycles_reader = Regex::new(r"Cycles: (\d*) Tag: (\d*)").unwrap();
 
+    let symboliser = backtrace::Symboliser::new(file_to_run).ok();
+    let mut profile = profile::Profile::default();
+    let mut scopes = profile::Scopes::default();
+
     let mut mgba = runner::MGBA::new(file_to_run).unwrap();
     let video_buffer = mgba.get_video_buffer();
     let mut number_of_cycles = Timing::None;
+    let mut reset_requested = false;
+    let mut has_reset = false;
 
     mgba.set_logger(|message| {
         if let Some(captures) = debug_reader_mutex.captures(message) {
@@ -49,6 +57,23 @@ fn test_file(file_to_run: &str) -> Status {
                     }
                     Ok(_) => {}
                 }
+            } else if let Some(addresses) = out.strip_prefix("Profile:") {
+                profile.add_sample(addresses);
+            } else if let Some(name) = out.strip_prefix("Scope tag:") {
+                scopes.add_name(name);
+            } else if let Some(addresses) = out.strip_prefix("Backtrace:") {
+                backtrace::print_backtrace(addresses, symboliser.as_ref());
+            } else if out == "Reset requested" {
+                // a test which keeps asking for a reset would otherwise run forever
+                if has_reset {
+                    println!("[failed]");
+                    println!("Reset requested more than once");
+                    finished = Status::Failed;
+                } else {
+                    println!("[reset]");
+                    has_reset = true;
+                    reset_requested = true;
+                }
             } else if out.ends_with("...") {
                 print!("{}", out);
                 io::stdout().flush().expect("can't flush stdout");
@@ -57,7 +82,9 @@ fn test_file(file_to_run: &str) -> Status {
                     let num_cycles: i32 = captures[1].parse().unwrap();
                     let tag: u16 = captures[2].parse().unwrap();
 
-                    if tag == TEST_RUNNER_TAG {
+                    if tag >= profile::FIRST_SCOPE_TAG {
+                        scopes.add_cycles(tag, num_cycles as i64);
+                    } else if tag == TEST_RUNNER_TAG {
                         number_of_cycles = match number_of_cycles {
                             Timing::WaitFor(n) => Timing::Difference(num_cycles - n),
                             Timing::None => Timing::WaitFor(num_cycles),
@@ -89,13 +116,33 @@ fn test_file(file_to_run: &str) -> Status {
         }
     });
 
+    let mut frame = 0;
     loop {
         mgba.advance_frame();
+        frame += 1;
+
+        if reset_requested {
+            reset_requested = false;
+            mgba.reset();
+        }
+
+        if Some(frame) == profile_frames {
+            finished = Status::Sucess;
+        }
+
         if finished != Status::Running {
             break;
         }
     }
 
+    if !profile.is_empty() {
+        profile.print_report(symboliser.as_ref());
+    }
+
+    if !scopes.is_empty() {
+        scopes.print_report();
+    }
+
     return finished;
 }
 
@@ -107,7 +154,19 @@ fn main() -> Result<(), Error> {
         return Err(anyhow!("File to run should exist!"));
     }
 
-    let output = test_file(file_to_run);
+    // `--profile <frames>` runs a game which is using `agb::interrupt::profiler`
+    // for the given number of frames, then prints a report of its hot functions
+    let profile_frames = match args.get(2).map(String::as_str) {
+        Some("--profile") => Some(
+            args.get(3)
+                .ok_or_else(|| anyhow!("--profile needs the number of frames to run for"))?
+                .parse()?,
+        ),
+        Some(arg) => return Err(anyhow!("Unknown argument {}", arg)),
+        None => None,
+    };
+
+    let output = test_file(file_to_run, profile_frames);
 
     match output {
         Status::Failed => Err(anyhow!("Tests failed!")),
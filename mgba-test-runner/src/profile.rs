@@ -0,0 +1,220 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::backtrace::{call_site, parse_address, Symboliser};
+
+/// The number of functions shown in each part of the report
+const REPORT_LENGTH: usize = 30;
+
+/// Samples logged by `agb::interrupt::profiler`. Each sample is the program
+/// counter when the timer interrupt happened, followed by the return addresses
+/// of the functions which were running at the time.
+#[derive(Default)]
+pub struct Profile {
+    samples: Vec<Vec<u32>>,
+}
+
+impl Profile {
+    /// Adds a sample from a message of space separated addresses.
+    pub fn add_sample(&mut self, addresses: &str) {
+        let sample: Vec<u32> = addresses
+            .split_whitespace()
+            .filter_map(parse_address)
+            .collect();
+
+        if !sample.is_empty() {
+            self.samples.push(sample);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Prints the functions which the most samples were in, both counting only
+    /// the function which was running (flat) and counting every function on the
+    /// stack (cumulative).
+    pub fn print_report(&self, symboliser: Option<&Symboliser>) {
+        let mut functions = FunctionCache {
+            symboliser,
+            functions: HashMap::new(),
+        };
+
+        let mut flat: HashMap<String, usize> = HashMap::new();
+        let mut cumulative: HashMap<String, usize> = HashMap::new();
+
+        for sample in &self.samples {
+            // the logged program counter is 4 bytes after the instruction which
+            // was about to run when the interrupt happened
+            let program_counter = sample[0].saturating_sub(4);
+            let running = functions.functions(program_counter);
+            *flat.entry(running[0].clone()).or_default() += 1;
+
+            let mut on_stack = HashSet::new();
+            on_stack.extend(running.iter().cloned());
+            for &return_address in &sample[1..] {
+                on_stack.extend(
+                    functions
+                        .functions(call_site(return_address))
+                        .iter()
+                        .cloned(),
+                );
+            }
+
+            for function in on_stack {
+                *cumulative.entry(function).or_default() += 1;
+            }
+        }
+
+        println!("Profile of {} samples", self.samples.len());
+        self.print_table("Flat", flat);
+        self.print_table("Cumulative", cumulative);
+    }
+
+    fn print_table(&self, title: &str, counts: HashMap<String, usize>) {
+        let mut counts: Vec<_> = counts.into_iter().collect();
+        counts.sort_by(|(a_name, a), (b_name, b)| b.cmp(a).then_with(|| a_name.cmp(b_name)));
+
+        println!();
+        println!("{}:", title);
+        println!("{:>8} {:>8}  function", "percent", "samples");
+
+        for (function, count) in counts.into_iter().take(REPORT_LENGTH) {
+            println!(
+                "{:>7.2}% {:>8}  {}",
+                count as f64 * 100.0 / self.samples.len() as f64,
+                count,
+                function
+            );
+        }
+    }
+}
+
+/// Looks up the functions at each address once, since the same few addresses
+/// come up many times in a profile.
+struct FunctionCache<'a> {
+    symboliser: Option<&'a Symboliser>,
+    functions: HashMap<u32, Vec<String>>,
+}
+
+impl FunctionCache<'_> {
+    /// The functions at the address, starting with the innermost one. This is
+    /// never empty, falling back to the address itself if it can't be found.
+    fn functions(&mut self, address: u32) -> &[String] {
+        let symboliser = self.symboliser;
+
+        self.functions.entry(address).or_insert_with(|| {
+            let functions: Vec<String> = symboliser
+                .and_then(|symboliser| symboliser.frames(address).ok())
+                .unwrap_or_default()
+                .into_iter()
+                .map(|frame| frame.function)
+                .collect();
+
+            if functions.is_empty() {
+                vec![format!("{:#010x}", address)]
+            } else {
+                functions
+            }
+        })
+    }
+}
+
+/// The first tag used by `agb::profile::scope!`. Each scope writes its start
+/// tag to mgba's cycle register when it is entered and the tag after it when it
+/// is left.
+pub const FIRST_SCOPE_TAG: u16 = 0x1000;
+
+struct ScopeTiming {
+    name: String,
+    started: Vec<i64>,
+    count: u64,
+    total: i64,
+    min: i64,
+    max: i64,
+}
+
+/// The cycles spent in each `agb::profile::scope!`, as counted by mgba.
+#[derive(Default)]
+pub struct Scopes {
+    scopes: HashMap<u16, ScopeTiming>,
+}
+
+impl Scopes {
+    /// Remembers the name of the scope with the given start tag, from a
+    /// message like `4096 physics`.
+    pub fn add_name(&mut self, message: &str) {
+        let (tag, name) = match message.trim().split_once(' ') {
+            Some(tag_and_name) => tag_and_name,
+            None => return,
+        };
+
+        if let Ok(tag) = tag.parse() {
+            self.scopes.insert(
+                tag,
+                ScopeTiming {
+                    name: name.to_owned(),
+                    started: Vec::new(),
+                    count: 0,
+                    total: 0,
+                    min: i64::MAX,
+                    max: 0,
+                },
+            );
+        }
+    }
+
+    /// Records the start or end of a scope from a tagged number of cycles.
+    pub fn add_cycles(&mut self, tag: u16, cycles: i64) {
+        let start_tag = tag & !1;
+        let scope = match self.scopes.get_mut(&start_tag) {
+            Some(scope) => scope,
+            None => return,
+        };
+
+        if tag == start_tag {
+            scope.started.push(cycles);
+        } else if let Some(start) = scope.started.pop() {
+            let taken = cycles - start;
+            scope.count += 1;
+            scope.total += taken;
+            scope.min = scope.min.min(taken);
+            scope.max = scope.max.max(taken);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scopes.values().all(|scope| scope.count == 0)
+    }
+
+    /// Prints the minimum, average and maximum cycles spent in each scope,
+    /// along with the average as a percentage of a frame.
+    pub fn print_report(&self) {
+        const CYCLES_PER_FRAME: f64 = 280_896.0;
+
+        let mut scopes: Vec<_> = self
+            .scopes
+            .values()
+            .filter(|scope| scope.count > 0)
+            .collect();
+        scopes.sort_by(|a, b| b.total.cmp(&a.total).then_with(|| a.name.cmp(&b.name)));
+
+        println!("Scopes:");
+        println!(
+            "{:>8} {:>10} {:>10} {:>10} {:>8}  scope",
+            "count", "min", "avg", "max", "frame"
+        );
+
+        for scope in scopes {
+            let average = scope.total / scope.count as i64;
+            println!(
+                "{:>8} {:>10} {:>10} {:>10} {:>7.2}%  {}",
+                scope.count,
+                scope.min,
+                average,
+                scope.max,
+                average as f64 * 100.0 / CYCLES_PER_FRAME,
+                scope.name
+            );
+        }
+    }
+}
@@ -58,6 +58,11 @@ impl MGBA {
     pub fn advance_frame(&mut self) {
         unsafe { bindings::advance_frame(self.mgba) }
     }
+    /// Resets the console without clearing the save data, so that games can
+    /// check it persists.
+    pub fn reset(&mut self) {
+        unsafe { bindings::reset(self.mgba) }
+    }
     pub fn set_logger(&mut self, mut logger: impl FnMut(&str)) {
         unsafe {
             let callback = generate_c_callback(move |message: *mut c_char| {